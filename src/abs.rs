@@ -6,44 +6,202 @@
  * copied, modified, or distributed except according to those terms.
  */
 //! The absolute path type, the root type for all `Path*` types in this module.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::env;
 use std::ffi;
 use std::fmt;
+use std::fs;
 use std::io;
-use std::path::{Component, PrefixComponent};
+use std::path::{Component, Prefix, PrefixComponent};
+use std::sync::{Mutex, MutexGuard, OnceLock};
 use std_prelude::*;
 
-use super::{Error, PathMut, PathOps, Result};
+use super::{lexical_normalize, Error, PathDir, PathMut, PathOps, Relative, Result};
 
 /// Converts any PrefixComponent into verbatim ("extended-length") form.
-fn make_verbatim_prefix(prefix: &PrefixComponent<'_>) -> Result<PathBuf> {
+///
+/// If the prefix is not followed by a root (e.g. `C:foo`), it is relative to the current
+/// directory of that drive, which is included in the result.
+fn make_verbatim_prefix(prefix: &PrefixComponent<'_>, has_root: bool) -> Result<PathBuf> {
     let path_prefix = Path::new(prefix.as_os_str());
 
     if prefix.kind().is_verbatim() {
         // This prefix already uses the extended-length
         // syntax, so we can use it as-is.
-        Ok(path_prefix.to_path_buf())
-    } else {
-        // This prefix needs canonicalization.
-        let res = path_prefix
+        return Ok(path_prefix.to_path_buf());
+    }
+
+    // This prefix needs canonicalization.
+    let canonicalize = || {
+        path_prefix
             .canonicalize()
-            .map_err(|e| Error::new(e, "canonicalizing", path_prefix.to_path_buf().into()))?;
-        Ok(res)
+            .map_err(|e| Error::new(e, "canonicalizing", path_prefix.to_path_buf().into()))
+    };
+    if !has_root {
+        return canonicalize();
+    }
+
+    // Only the prefix itself is needed, and the canonical form of a drive or share is stable for
+    // the life of the process, so it is only computed once per distinct prefix.
+    let cache = PREFIX_CACHE.get_or_init(Default::default);
+    if let Some(res) = lock_prefix_cache(cache).get(prefix.as_os_str()) {
+        return Ok(res.clone());
+    }
+    let canonical = canonicalize()?;
+    let res = match canonical.components().next() {
+        Some(Component::Prefix(p)) => PathBuf::from(p.as_os_str()),
+        _ => canonical,
+    };
+    lock_prefix_cache(cache).insert(prefix.as_os_str().to_os_string(), res.clone());
+    Ok(res)
+}
+
+/// The canonicalized form of each non-verbatim prefix (e.g. `C:` => `\\?\C:`) seen so far.
+static PREFIX_CACHE: OnceLock<Mutex<HashMap<ffi::OsString, PathBuf>>> = OnceLock::new();
+
+fn lock_prefix_cache(
+    cache: &Mutex<HashMap<ffi::OsString, PathBuf>>,
+) -> MutexGuard<'_, HashMap<ffi::OsString, PathBuf>> {
+    // The map is always left consistent, so a panic while it was locked doesn't matter.
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+thread_local! {
+    /// The last `(current_dir, canonicalized current_dir)` pair seen on this thread.
+    static CWD_CACHE: RefCell<Option<(PathBuf, PathBuf)>> = const { RefCell::new(None) };
+}
+
+/// Returns the canonicalized current directory.
+///
+/// Canonicalizing is much more expensive than getting the `current_dir`, so the result is cached
+/// per thread and only recomputed when the raw `current_dir` changes.
+fn canonical_current_dir(resolvee: &Arc<PathBuf>) -> Result<PathBuf> {
+    let raw = env::current_dir().map_err(|e| {
+        Error::new(
+            e,
+            "getting current_dir while resolving absolute",
+            resolvee.clone(),
+        )
+    })?;
+
+    let cached = CWD_CACHE.with(|cache| match *cache.borrow() {
+        Some((ref cached_raw, ref canonical)) if *cached_raw == raw => Some(canonical.clone()),
+        _ => None,
+    });
+    if let Some(canonical) = cached {
+        return Ok(canonical);
     }
+
+    let canonical = raw
+        .canonicalize()
+        .map_err(|e| Error::new(e, "canonicalizing", raw.clone().into()))?;
+    CWD_CACHE.with(|cache| *cache.borrow_mut() = Some((raw, canonical.clone())));
+    Ok(canonical)
 }
 
-/// Pops the last component from path, returning an error for a root path.
-fn pop_or_error(path: &mut PathBuf) -> ::std::result::Result<(), io::Error> {
-    if path.pop() {
-        Ok(())
+/// Percent-encode a path segment for a URL, keeping the characters RFC 3986 allows unencoded.
+///
+/// Returns `None` if the segment is not unicode on a platform where that matters.
+fn percent_encode(segment: &ffi::OsStr) -> Option<String> {
+    let bytes = if cfg!(unix) {
+        // On unix the encoded bytes are the raw bytes of the path.
+        segment.as_encoded_bytes()
     } else {
-        Err(io::Error::new(io::ErrorKind::NotFound, ".. consumed root"))
+        segment.to_str()?.as_bytes()
+    };
+    let mut out = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => out.push(b as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    Some(out)
+}
+
+/// Decode the `%XX` escapes in a URL, returning `None` if one is malformed.
+fn percent_decode(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            // `from_str_radix` would also accept a sign, e.g. `%+1`.
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(out)
+}
+
+/// Convert raw bytes decoded from a URL into a path.
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(ffi::OsString::from_vec(bytes).into())
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Returns whether the component is a reserved device name on windows.
+fn is_reserved_name(component: &ffi::OsStr) -> bool {
+    let name = match component.to_str() {
+        Some(n) => n,
+        None => return false,
+    };
+    // The name is reserved even with an extension or trailing spaces.
+    let base = name.split('.').next().unwrap_or(name).trim_end_matches(' ');
+    let upper = base.to_ascii_uppercase();
+    match upper.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        _ => {
+            let bytes = upper.as_bytes();
+            bytes.len() == 4
+                && (upper.starts_with("COM") || upper.starts_with("LPT"))
+                && (b'1'..=b'9').contains(&bytes[3])
+        }
     }
 }
 
 #[derive(Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
 /// An absolute (not _necessarily_ [canonicalized][1]) path that may or may not exist.
 ///
+/// Paths are ordered component-by-component (like `Path`), so a directory sorts immediately
+/// before everything inside it, which makes a sorted list of paths easy to build a tree from.
+///
+/// ```rust
+/// # extern crate path_abs;
+/// use std::path::PathBuf;
+/// use path_abs::PathAbs;
+///
+/// let mut paths = vec![
+///     PathAbs::new_unchecked(PathBuf::from("/a-b")),
+///     PathAbs::new_unchecked(PathBuf::from("/a/b/c")),
+///     PathAbs::new_unchecked(PathBuf::from("/a/b")),
+///     PathAbs::new_unchecked(PathBuf::from("/a")),
+/// ];
+/// paths.sort();
+///
+/// let sorted: Vec<_> = paths.iter().map(|p| p.as_path().to_str().unwrap()).collect();
+/// assert_eq!(sorted, ["/a", "/a/b", "/a/b/c", "/a-b"]);
+/// ```
+///
 /// [1]: https://doc.rust-lang.org/std/path/struct.Path.html?search=#method.canonicalize
 pub struct PathAbs(pub(crate) Arc<PathBuf>);
 
@@ -65,7 +223,8 @@ impl PathAbs {
     /// > the [`current_dir`]
     ///
     /// > On linux, the only syscall this will make is to get the [`current_dir`] for relative
-    /// > paths.
+    /// > paths. The canonicalized current directory is cached per thread and only recomputed when
+    /// > the [`current_dir`] changes.
     ///
     /// [`canonicalize`]: struct.PathAbs.html#method.canonicalize
     /// [`current_dir`]: fn.current_dir.html
@@ -82,10 +241,114 @@ impl PathAbs {
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
     pub fn new<P: AsRef<Path>>(path: P) -> Result<PathAbs> {
-        let path = Arc::new(path.as_ref().to_path_buf());
+        PathAbs::resolve(path.as_ref(), &mut None)
+    }
+
+    /// Construct an absolute path like [`new`], but resolve relative paths against `base` instead
+    /// of the current working directory.
+    ///
+    /// Absolute paths ignore `base`. An empty path or `.` resolves to `base` itself. This never
+    /// gets the process's current working directory, except for drive-relative paths on Windows
+    /// (e.g. `C:foo`), which are resolved against the current directory of that drive.
+    ///
+    /// [`new`]: struct.PathAbs.html#method.new
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use path_abs::{PathAbs, PathDir, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    ///
+    /// let lib = PathAbs::new_relative_to("lib.rs", &src)?;
+    /// assert_eq!(lib, src.concat("lib.rs")?);
+    ///
+    /// let cargo = PathAbs::new_relative_to("../Cargo.toml", &src)?;
+    /// assert_eq!(cargo, PathAbs::new("Cargo.toml")?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn new_relative_to<P: AsRef<Path>>(path: P, base: &PathDir) -> Result<PathAbs> {
+        PathAbs::resolve(path.as_ref(), &mut Some(base.as_path().to_path_buf()))
+    }
+
+    /// Construct an absolute path by joining the relative path `rel` onto `root`, guaranteeing
+    /// that the result is inside `root`.
+    ///
+    /// `.` and `..` components are resolved lexically, so `rel` doesn't have to exist. If `rel`
+    /// has a prefix or root this returns `io::ErrorKind::InvalidInput`, and if it would leave
+    /// `root` (e.g. `../../etc/passwd`) it returns `io::ErrorKind::PermissionDenied`. This makes
+    /// it safe to use with untrusted input, such as paths received over the network.
+    ///
+    /// > Symlinks inside `root` are not resolved, so they may still point outside of it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    /// use path_abs::{PathAbs, PathDir, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    ///
+    /// let lib = PathAbs::new_under(&src, "foo/../lib.rs")?;
+    /// assert_eq!(lib, src.concat("lib.rs")?);
+    ///
+    /// let err = PathAbs::new_under(&src, "../Cargo.toml").unwrap_err();
+    /// assert_eq!(io::ErrorKind::PermissionDenied, err.io_error().kind());
+    /// assert!(PathAbs::new_under(&src, "/etc/passwd").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn new_under<P: AsRef<Path>>(root: &PathDir, rel: P) -> Result<PathAbs> {
+        let action = format!("resolving under {}", root.as_path().display());
+        let rel = Relative::validate(rel.as_ref(), io::ErrorKind::PermissionDenied)
+            .map_err(|err| err.with_action(&action))?;
+        root.concat(rel)
+    }
+
+    /// Construct many absolute paths at once, see [`new`].
+    ///
+    /// The current working directory is fetched and canonicalized at most once and reused for
+    /// every relative input, which is considerably faster than calling [`new`] in a loop.
+    ///
+    /// [`new`]: struct.PathAbs.html#method.new
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use path_abs::{PathAbs, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let paths = PathAbs::new_batch(&["src/lib.rs", "src/abs.rs", "Cargo.toml"])?;
+    ///
+    /// assert_eq!(paths.len(), 3);
+    /// assert!(paths.iter().all(|p| p.is_absolute()));
+    /// assert_eq!(paths[1], PathAbs::new("src/abs.rs")?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn new_batch<I, P>(paths: I) -> Result<Vec<PathAbs>>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut cwd = None;
+        paths
+            .into_iter()
+            .map(|path| PathAbs::resolve(path.as_ref(), &mut cwd))
+            .collect()
+    }
+
+    /// Resolve `path` into an absolute path, lazily filling in `cwd` with the canonicalized
+    /// current directory the first time it is needed.
+    fn resolve(path: &Path, cwd: &mut Option<PathBuf>) -> Result<PathAbs> {
+        let path = Arc::new(path.to_path_buf());
         let mut res = PathBuf::new();
 
-        fn maybe_init_res(res: &mut PathBuf, resolvee: Arc<PathBuf>) -> Result<()> {
+        fn maybe_init_res(
+            res: &mut PathBuf,
+            cwd: &mut Option<PathBuf>,
+            resolvee: &Arc<PathBuf>,
+        ) -> Result<()> {
             if !res.as_os_str().is_empty() {
                 // res has already been initialized, let's leave it alone.
                 return Ok(());
@@ -93,67 +356,96 @@ impl PathAbs {
 
             // res has not been initialized, let's initialize it to the
             // canonicalized current directory.
-            let cwd = env::current_dir().map_err(|e| {
-                Error::new(e, "getting current_dir while resolving absolute", resolvee)
-            })?;
-            *res = cwd
-                .canonicalize()
-                .map_err(|e| Error::new(e, "canonicalizing", cwd.into()))?;
+            let canonical = match cwd.take() {
+                Some(canonical) => canonical,
+                None => canonical_current_dir(resolvee)?,
+            };
+            *res = canonical.clone();
+            *cwd = Some(canonical);
 
             Ok(())
         };
 
-        for each in path.components() {
-            match each {
-                Component::Prefix(p) => {
-                    // We don't care what's already in res, we can entirely
-                    // replace it..
-                    res = make_verbatim_prefix(&p)?;
-                }
+        let mut components = path.components().peekable();
+        if let Some(&Component::Prefix(p)) = components.peek() {
+            components.next();
+            let has_root = components.peek() == Some(&Component::RootDir);
+            res = make_verbatim_prefix(&p, has_root)?;
+        }
 
-                Component::RootDir => {
-                    if cfg!(windows) {
-                        // In an ideal world, we would say
-                        //
-                        //  res = std::fs::canonicalize(each)?;
-                        //
-                        // ...to get a properly canonicalized path.
-                        // Unfortunately, Windows cannot canonicalize `\` if
-                        // the current directory happens to use extended-length
-                        // syntax (like `\\?\C:\Windows`), so we'll have to do
-                        // it manually: initialize `res` with the current
-                        // working directory (whatever it is), and truncate it
-                        // to its prefix by pushing `\`.
-                        maybe_init_res(&mut res, path.clone())?;
-                        res.push(each);
-                    } else {
-                        // On other platforms, a root path component is always
-                        // absolute so we can replace whatever's in res.
-                        res = Path::new(&each).to_path_buf();
-                    }
-                }
+        if let Some(&Component::RootDir) = components.peek() {
+            if cfg!(windows) {
+                // In an ideal world, we would say
+                //
+                //  res = std::fs::canonicalize(each)?;
+                //
+                // ...to get a properly canonicalized path.
+                // Unfortunately, Windows cannot canonicalize `\` if
+                // the current directory happens to use extended-length
+                // syntax (like `\\?\C:\Windows`), so we'll have to do
+                // it manually: initialize `res` with the current
+                // working directory (whatever it is), and truncate it
+                // to its prefix by pushing `\`.
+                maybe_init_res(&mut res, cwd, &path)?;
+                res.push(Component::RootDir);
+            } else {
+                // On other platforms, a root path component is always
+                // absolute so we can replace whatever's in res.
+                res = PathBuf::from(Component::RootDir.as_os_str());
+            }
+            components.next();
+        }
 
-                // This does nothing and can be ignored.
-                Component::CurDir => (),
+        // Everything else is relative to the base we have so far, or to the current directory if
+        // there is none. An empty path or one with only `.` components is that base itself.
+        maybe_init_res(&mut res, cwd, &path)?;
+        lexical_normalize(&mut res, components)
+            .map_err(|e| Error::new(e, "resolving absolute", path.clone()))?;
 
-                Component::ParentDir => {
-                    // A parent component is always relative to some existing
-                    // path.
-                    maybe_init_res(&mut res, path.clone())?;
-                    pop_or_error(&mut res)
-                        .map_err(|e| Error::new(e, "resolving absolute", path.clone()))?;
-                }
+        Ok(PathAbs(Arc::new(res)))
+    }
 
-                Component::Normal(c) => {
-                    // A normal component is always relative to some existing
-                    // path.
-                    maybe_init_res(&mut res, path.clone())?;
-                    res.push(c);
+    /// Construct an absolute path like [`new`], additionally rejecting paths which would behave
+    /// surprisingly on the current platform.
+    ///
+    /// On windows this returns `io::ErrorKind::InvalidInput` if any component is a reserved device
+    /// name (`CON`, `PRN`, `AUX`, `NUL`, `COM1`-`COM9` or `LPT1`-`LPT9`), case-insensitively and
+    /// regardless of extension (`nul.txt` is still `NUL`). On other platforms it is the same as
+    /// [`new`].
+    ///
+    /// [`new`]: struct.PathAbs.html#method.new
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new_validated("src/lib.rs")?;
+    ///
+    /// if cfg!(windows) {
+    ///     assert!(PathAbs::new_validated("src/con.rs").is_err());
+    /// }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn new_validated<P: AsRef<Path>>(path: P) -> Result<PathAbs> {
+        if cfg!(windows) {
+            for each in path.as_ref().components() {
+                if let Component::Normal(c) = each {
+                    if is_reserved_name(c) {
+                        return Err(Error::new(
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                "path contains a reserved device name",
+                            ),
+                            "validating",
+                            path.as_ref().to_path_buf().into(),
+                        ));
+                    }
                 }
             }
         }
-
-        Ok(PathAbs(Arc::new(res)))
+        PathAbs::new(path)
     }
 
     /// Create a PathAbs unchecked.
@@ -171,6 +463,328 @@ impl PathAbs {
     pub fn as_path(&self) -> &Path {
         self.as_ref()
     }
+
+    /// Return the path as a `&str` if it is valid UTF-8, without allocating.
+    ///
+    /// This is the same as `self.as_path().to_str()`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// assert!(lib.as_str().unwrap().ends_with("lib.rs"));
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_path().to_str()
+    }
+
+    /// Convert the path into a percent-encoded `file://` URL, as used by browsers, editors and
+    /// LSP clients.
+    ///
+    /// On Windows, drive paths become `file:///C:/...` and UNC paths `file://server/share/...`.
+    /// Other verbatim or device paths, and (on Windows only) paths which are not valid unicode,
+    /// return `io::ErrorKind::InvalidInput`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # #[cfg(unix)] {
+    /// let path = PathAbs::new("/home/user/my file#1.txt")?;
+    /// let url = path.to_file_url()?;
+    /// assert_eq!("file:///home/user/my%20file%231.txt", url);
+    /// assert_eq!(path, PathAbs::from_file_url(&url)?);
+    /// # }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn to_file_url(&self) -> Result<String> {
+        let err = |msg| {
+            Error::new(
+                io::Error::new(io::ErrorKind::InvalidInput, msg),
+                "converting to file URL",
+                self.clone().into(),
+            )
+        };
+        let mut host = String::new();
+        let mut path = String::new();
+        for component in self.as_path().components() {
+            let segment = match component {
+                Component::Prefix(prefix) => match prefix.kind() {
+                    Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+                        format!("{}:", drive as char)
+                    }
+                    Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                        host = percent_encode(server).ok_or_else(|| err("path is not unicode"))?;
+                        percent_encode(share).ok_or_else(|| err("path is not unicode"))?
+                    }
+                    _ => return Err(err("prefix cannot be represented in a file URL")),
+                },
+                Component::RootDir | Component::CurDir => continue,
+                Component::ParentDir => "..".to_string(),
+                Component::Normal(name) => {
+                    percent_encode(name).ok_or_else(|| err("path is not unicode"))?
+                }
+            };
+            path.push('/');
+            path.push_str(&segment);
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        Ok(format!("file://{}{}", host, path))
+    }
+
+    /// Parse a `file://` URL back into an absolute path, the inverse of [`to_file_url`].
+    ///
+    /// Percent-encoded bytes are decoded within each `/`-separated segment, and a segment which
+    /// decodes to a separator or NUL is rejected. Any query or fragment is ignored. A host other than
+    /// `localhost` is only allowed on Windows, where it becomes a UNC path. Anything else which
+    /// is not an absolute `file://` URL returns `io::ErrorKind::InvalidInput`.
+    ///
+    /// [`to_file_url`]: #method.to_file_url
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # #[cfg(unix)] {
+    /// let path = PathAbs::from_file_url("file:///tmp/caf%C3%A9.txt")?;
+    /// assert_eq!(PathAbs::new("/tmp/café.txt")?, path);
+    /// # }
+    /// # #[cfg(windows)] {
+    /// let path = PathAbs::from_file_url("file:///C:/Program%20Files")?;
+    /// assert_eq!(PathAbs::new(r"C:\Program Files")?, path);
+    /// # }
+    /// assert!(PathAbs::from_file_url("https://example.com/").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn from_file_url(url: &str) -> Result<PathAbs> {
+        let err = |msg| {
+            Error::new(
+                io::Error::new(io::ErrorKind::InvalidInput, msg),
+                "parsing file URL",
+                PathBuf::from(url).into(),
+            )
+        };
+        let rest = match url.get(..7) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("file://") => &url[7..],
+            _ => return Err(err("not a file:// URL")),
+        };
+        let rest = rest.split(['?', '#']).next().unwrap_or("");
+        let (host, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => return Err(err("file URL has no path")),
+        };
+        let host = if host.eq_ignore_ascii_case("localhost") {
+            ""
+        } else {
+            host
+        };
+        // Decode each segment on its own, so an escaped separator can't introduce a new one.
+        let separators: &[u8] = if cfg!(windows) { b"/\\\0" } else { b"/\0" };
+        let mut segments = Vec::new();
+        for segment in path.split('/') {
+            let segment = percent_decode(segment).ok_or_else(|| err("invalid percent-encoding"))?;
+            if segment.iter().any(|b| separators.contains(b)) {
+                return Err(err("file URL has an escaped separator or NUL"));
+            }
+            segments.push(segment);
+        }
+
+        let path = if cfg!(windows) {
+            let segments = segments
+                .into_iter()
+                .map(String::from_utf8)
+                .collect::<::std::result::Result<Vec<_>, _>>()
+                .map_err(|_| err("path is not unicode"))?;
+            let path = segments.join("\\");
+            if host.is_empty() {
+                // `/C:/foo` => `C:\foo`
+                path.trim_start_matches('\\').to_string()
+            } else {
+                let host = percent_decode(host)
+                    .and_then(|h| String::from_utf8(h).ok())
+                    .ok_or_else(|| err("invalid host"))?;
+                format!(r"\\{}{}", host, path)
+            }
+            .into()
+        } else {
+            if !host.is_empty() {
+                return Err(err("file URL has a remote host"));
+            }
+            bytes_to_path(segments.join(&b'/')).ok_or_else(|| err("path is not unicode"))?
+        };
+        if !path.is_absolute() {
+            return Err(err("file URL is not absolute"));
+        }
+        PathAbs::new(path)
+    }
+
+    /// Strip `base` off the front of the path, returning the rest as a validated [`Relative`].
+    ///
+    /// `base` is resolved with [`new`] first, so it may be relative to the current directory.
+    /// The result can safely be joined onto another directory, which makes this the building
+    /// block for relocating a file under a new root.
+    ///
+    /// Returns `io::ErrorKind::InvalidInput` if `base` is not a prefix of the path.
+    ///
+    /// [`Relative`]: struct.Relative.html
+    /// [`new`]: #method.new
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use std::path::Path;
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// assert_eq!(Path::new("lib.rs"), lib.strip_prefix_abs("src")?.as_path());
+    ///
+    /// assert!(lib.strip_prefix_abs("tests").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn strip_prefix_abs<P: AsRef<Path>>(&self, base: P) -> Result<Relative> {
+        let base = PathAbs::new(base)?;
+        let stripped = Path::strip_prefix(self.as_path(), &base).map_err(|_| {
+            Error::new(
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "base is not a prefix of the path",
+                ),
+                &format!("stripping {} from", base.as_path().display()),
+                self.clone().into(),
+            )
+        })?;
+        Relative::new(stripped)
+    }
+
+    /// Move the path from under `from` to the same place under `to`.
+    ///
+    /// This strips `from` like [`strip_prefix_abs`] and concatenates the rest onto `to`. Both
+    /// `from` and `to` are resolved with [`new`], so they may be relative to the current
+    /// directory. Returns `io::ErrorKind::InvalidInput` if the path is not under `from`.
+    ///
+    /// [`strip_prefix_abs`]: #method.strip_prefix_abs
+    /// [`new`]: #method.new
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathAbs;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// let staged = lib.rebase("src", "target/staging")?;
+    /// assert_eq!(PathAbs::new("target/staging/lib.rs")?, staged);
+    ///
+    /// assert!(lib.rebase("tests", "target/staging").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn rebase<P, Q>(&self, from: P, to: Q) -> Result<PathAbs>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let relative = self.strip_prefix_abs(from)?;
+        PathAbs::new(to)?.concat(relative)
+    }
+
+    /// Get the parent directory of this path as a `PathDir`.
+    ///
+    /// > This does not make aditional syscalls, as the parent of an absolute path must be a
+    /// > directory if it exists.
+    ///
+    /// Returns `None` if this is the root.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathAbs, PathDir};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// let src = lib.parent_dir().unwrap();
+    /// assert_eq!(PathDir::new("src")?, src);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn parent_dir(&self) -> Option<PathDir> {
+        self.as_path()
+            .parent()
+            .map(|path| PathDir(PathAbs(Arc::new(path.to_path_buf()))))
+    }
+
+    /// Iterate over this path and each of its ancestors as owned `PathAbs` values, ending at the
+    /// root.
+    ///
+    /// This is the owned counterpart to [`PathInfo::ancestors`] and does not touch the
+    /// filesystem.
+    ///
+    /// [`PathInfo::ancestors`]: trait.PathInfo.html#method.ancestors
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathAbs, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// let mut ancestors = lib.ancestors_abs();
+    /// assert_eq!(ancestors.next(), Some(lib.clone()));
+    /// assert_eq!(ancestors.next(), Some(PathAbs::new("src")?));
+    /// assert_eq!(ancestors.last().unwrap().parent().ok(), None);
+    ///
+    /// // Search upward for the directory containing `Cargo.toml`.
+    /// let root = lib
+    ///     .ancestors_abs()
+    ///     .find(|p| p.as_path().join("Cargo.toml").exists());
+    /// assert_eq!(root.unwrap().concat("src/lib.rs")?, lib);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn ancestors_abs(&self) -> impl Iterator<Item = PathAbs> {
+        ::std::iter::successors(Some(self.clone()), |path| {
+            let mut parent = path.clone();
+            if Arc::make_mut(&mut parent.0).pop() {
+                Some(parent)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Iterate over each successive parent of this path as a `PathAbs`, ending with the root.
+    ///
+    /// This is [`ancestors_abs`] without the path itself, and does not touch the filesystem.
+    ///
+    /// [`ancestors_abs`]: #method.ancestors_abs
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathAbs, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathAbs::new("src/lib.rs")?;
+    /// let mut parents = lib.parents();
+    /// assert_eq!(parents.next(), Some(PathAbs::new("src")?));
+    /// assert_eq!(parents.next(), Some(PathAbs::new(".")?));
+    /// assert!(parents.last().unwrap().parent().is_err());
+    ///
+    /// let root = PathAbs::new("src")?.parents().last().unwrap();
+    /// assert_eq!(root.parents().next(), None);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn parents(&self) -> impl Iterator<Item = PathAbs> {
+        self.ancestors_abs().skip(1)
+    }
 }
 
 impl fmt::Debug for PathAbs {
@@ -195,6 +809,12 @@ impl PathMut for PathAbs {
     fn set_extension<S: AsRef<ffi::OsStr>>(&mut self, extension: S) -> bool {
         self.0.set_extension(extension)
     }
+    fn set_file_stem<S: AsRef<ffi::OsStr>>(&mut self, stem: S) {
+        self.0.set_file_stem(stem)
+    }
+    fn normalize(&mut self) -> Result<()> {
+        self.0.normalize()
+    }
 }
 
 impl PathOps for PathAbs {
@@ -209,6 +829,22 @@ impl PathOps for PathAbs {
         Self::Output::new_unchecked(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        Ok(PathAbs(self.0.concat_all(parts)?))
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        PathAbs(self.0.join_all(parts))
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         PathAbs(self.0.with_file_name(file_name))
     }
@@ -216,6 +852,14 @@ impl PathOps for PathAbs {
     fn with_extension<S: AsRef<ffi::OsStr>>(&self, extension: S) -> Self::Output {
         PathAbs(self.0.with_extension(extension))
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        PathAbs(self.0.with_file_stem(stem))
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        Ok(PathAbs(self.0.normalized()?))
+    }
 }
 
 impl AsRef<ffi::OsStr> for PathAbs {
@@ -274,3 +918,293 @@ impl From<PathAbs> for PathBuf {
         }
     }
 }
+
+/// Resolve an `OsString`, e.g. from `env::args_os`, the same as [`PathAbs::new`].
+///
+/// [`PathAbs::new`]: struct.PathAbs.html#method.new
+///
+/// # Examples
+/// ```rust
+/// # extern crate path_abs;
+/// use std::convert::TryFrom;
+/// use std::ffi::OsString;
+/// use path_abs::PathAbs;
+///
+/// # fn try_main() -> ::std::io::Result<()> {
+/// let lib = PathAbs::try_from(OsString::from("src/lib.rs"))?;
+/// assert_eq!(PathAbs::new("src/lib.rs")?, lib);
+/// # Ok(()) } fn main() { try_main().unwrap() }
+/// ```
+impl TryFrom<ffi::OsString> for PathAbs {
+    type Error = Error;
+
+    fn try_from(path: ffi::OsString) -> Result<PathAbs> {
+        PathAbs::new(path)
+    }
+}
+
+/// Resolve an `&OsStr` the same as [`PathAbs::new`].
+///
+/// [`PathAbs::new`]: struct.PathAbs.html#method.new
+impl<'a> TryFrom<&'a ffi::OsStr> for PathAbs {
+    type Error = Error;
+
+    fn try_from(path: &'a ffi::OsStr) -> Result<PathAbs> {
+        PathAbs::new(path)
+    }
+}
+
+/// Canonicalizes many paths, remembering the canonical form of the directories it has seen.
+///
+/// Canonicalizing `/a/b/c/x` and then `/a/b/c/y` with [`PathInfo::canonicalize`] resolves
+/// `/a/b/c` twice. A `Canonicalizer` resolves each directory prefix once and reuses it, only
+/// checking the final component of later paths.
+///
+/// The cache is never invalidated, so only keep a `Canonicalizer` around while the directories it
+/// has seen are not being changed (or call [`clear`]). Relative paths are not cached.
+///
+/// [`PathInfo::canonicalize`]: trait.PathInfo.html#method.canonicalize
+/// [`clear`]: struct.Canonicalizer.html#method.clear
+///
+/// # Examples
+/// ```rust
+/// # extern crate path_abs;
+/// use path_abs::{Canonicalizer, PathAbs, PathInfo};
+///
+/// # fn try_main() -> ::std::io::Result<()> {
+/// let src = PathAbs::new("src")?;
+/// let mut canonicalizer = Canonicalizer::new();
+///
+/// for name in &["lib.rs", "abs.rs", "dir.rs"] {
+///     let path = src.as_path().join(name);
+///     assert_eq!(canonicalizer.canonicalize(&path)?, path.canonicalize()?);
+/// }
+/// # Ok(()) } fn main() { try_main().unwrap() }
+/// ```
+#[derive(Debug, Default)]
+pub struct Canonicalizer {
+    dirs: HashMap<PathBuf, PathBuf>,
+}
+
+impl Canonicalizer {
+    /// Create a `Canonicalizer` with an empty cache.
+    pub fn new() -> Canonicalizer {
+        Canonicalizer::default()
+    }
+
+    /// Returns the canonical, absolute form of the path, the same as
+    /// [`PathInfo::canonicalize`].
+    ///
+    /// [`PathInfo::canonicalize`]: trait.PathInfo.html#method.canonicalize
+    pub fn canonicalize<P: AsRef<Path>>(&mut self, path: P) -> Result<PathAbs> {
+        let path = path.as_ref();
+        self.resolve(path, false)
+            .map(|path| PathAbs(Arc::new(path)))
+            .map_err(|err| Error::new(err, "canonicalizing", path.to_path_buf().into()))
+    }
+
+    /// Forget every directory seen so far.
+    pub fn clear(&mut self) {
+        self.dirs.clear()
+    }
+
+    /// Canonicalize `path` using the cached parent, caching the result if `is_dir`.
+    fn resolve(&mut self, path: &Path, is_dir: bool) -> io::Result<PathBuf> {
+        if let Some(canonical) = self.dirs.get(path) {
+            return Ok(canonical.clone());
+        }
+
+        let canonical = match (path.parent(), path.components().next_back()) {
+            (Some(parent), Some(Component::Normal(name))) if path.is_absolute() => {
+                let candidate = self.resolve(parent, true)?.join(name);
+                if fs::symlink_metadata(&candidate)?.file_type().is_symlink() {
+                    fs::canonicalize(&candidate)?
+                } else {
+                    candidate
+                }
+            }
+            _ => fs::canonicalize(path)?,
+        };
+
+        if is_dir && path.is_absolute() {
+            self.dirs.insert(path.to_path_buf(), canonical.clone());
+        }
+        Ok(canonical)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn relative_to_base() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let base = PathDir::new(tmp_dir.path()).unwrap();
+
+        for each in &[".", "", "./.", "foo/.."] {
+            assert_eq!(
+                PathAbs::new_relative_to(each, &base).unwrap(),
+                PathAbs::from(base.clone()),
+                "{:?}",
+                each
+            );
+        }
+        assert_eq!(
+            PathAbs::new_relative_to("foo/../bar", &base)
+                .unwrap()
+                .as_path(),
+            base.as_path().join("bar")
+        );
+        assert_eq!(
+            PathAbs::new_relative_to(&base, &PathDir::new("src").unwrap()).unwrap(),
+            PathAbs::from(base.clone())
+        );
+    }
+
+    #[test]
+    fn new_cur_dir() {
+        assert_eq!(
+            PathAbs::new(".").unwrap().as_path(),
+            env::current_dir().unwrap().canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn canonicalizer_reuses_dirs() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let nested = tmp_dir.path().join("a").join("b").join("c");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("x"), "").unwrap();
+        fs::write(nested.join("y"), "").unwrap();
+
+        let mut canonicalizer = Canonicalizer::new();
+        for name in &["x", "y"] {
+            let path = nested.join(name);
+            assert_eq!(
+                canonicalizer.canonicalize(&path).unwrap().as_path(),
+                fs::canonicalize(&path).unwrap()
+            );
+        }
+        assert!(canonicalizer.dirs.contains_key(&nested));
+        let cached = canonicalizer.dirs.len();
+
+        canonicalizer.canonicalize(nested.join("x")).unwrap();
+        assert_eq!(canonicalizer.dirs.len(), cached);
+
+        let err = canonicalizer
+            .canonicalize(nested.join("missing"))
+            .expect_err("missing file");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonicalizer_symlinks() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let real = tmp_dir.path().join("real");
+        fs::create_dir(&real).unwrap();
+        fs::write(real.join("x"), "").unwrap();
+        ::std::os::unix::fs::symlink(&real, tmp_dir.path().join("link")).unwrap();
+
+        let mut canonicalizer = Canonicalizer::new();
+        let through_link = tmp_dir.path().join("link").join("x");
+        assert_eq!(
+            canonicalizer.canonicalize(&through_link).unwrap().as_path(),
+            fs::canonicalize(&through_link).unwrap()
+        );
+    }
+
+    /// On Unix a backslash is an ordinary filename character, not a separator.
+    #[cfg(unix)]
+    #[test]
+    fn backslash_is_not_a_separator() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let base = PathDir::new(tmp_dir.path()).unwrap();
+
+        let abs = PathAbs::new_relative_to(r"foo\bar", &base).unwrap();
+        assert_eq!(abs.as_path(), base.as_path().join(r"foo\bar"));
+        assert_eq!(abs.as_path().file_name(), Some(ffi::OsStr::new(r"foo\bar")));
+        assert_eq!(abs.as_path().parent(), Some(base.as_path()));
+
+        let abs = PathAbs::new_relative_to(r"foo\..\bar", &base).unwrap();
+        assert_eq!(
+            abs.as_path().file_name(),
+            Some(ffi::OsStr::new(r"foo\..\bar"))
+        );
+    }
+
+    #[test]
+    fn reserved_names() {
+        for name in &[
+            "CON",
+            "con",
+            "Nul.txt",
+            "aux.tar.gz",
+            "COM1",
+            "lpt9",
+            "PRN ",
+        ] {
+            assert!(is_reserved_name(ffi::OsStr::new(name)), "{}", name);
+        }
+        for name in &["CONSOLE", "COM", "COM0", "LPT10", "foo.con", "nul_", ""] {
+            assert!(!is_reserved_name(ffi::OsStr::new(name)), "{}", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_url_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = PathAbs::new("/").unwrap();
+        assert_eq!("file:///", root.to_file_url().unwrap());
+        assert_eq!(root, PathAbs::from_file_url("file://localhost/").unwrap());
+
+        let raw = ffi::OsStr::from_bytes(b"/tmp/a%b/\xFF?x");
+        let path = PathAbs::new(raw).unwrap();
+        let url = path.to_file_url().unwrap();
+        assert_eq!("file:///tmp/a%25b/%FF%3Fx", url);
+        assert_eq!(path, PathAbs::from_file_url(&url).unwrap());
+
+        assert!(PathAbs::from_file_url("file://server/share").is_err());
+        assert!(PathAbs::from_file_url("file:///bad%zz").is_err());
+        assert!(PathAbs::from_file_url("file:///bad%+1").is_err());
+
+        // A backslash is an ordinary character on unix.
+        let path = PathAbs::from_file_url("file:///tmp/a%5Cb").unwrap();
+        assert_eq!(Some(ffi::OsStr::new("a\\b")), path.as_path().file_name());
+    }
+
+    #[test]
+    fn file_url_rejects_escaped_separators() {
+        let kind = |url| PathAbs::from_file_url(url).unwrap_err().io_error().kind();
+        let prefix = if cfg!(windows) {
+            "file:///C:"
+        } else {
+            "file://"
+        };
+
+        let escape = format!("{}/a/b%2F..%2F..%2Fetc", prefix);
+        assert_eq!(io::ErrorKind::InvalidInput, kind(&escape));
+        let nul = format!("{}/a/b%00c", prefix);
+        assert_eq!(io::ErrorKind::InvalidInput, kind(&nul));
+        if cfg!(windows) {
+            let backslash = format!("{}/a/b%5C..%5C..%5CWindows", prefix);
+            assert_eq!(io::ErrorKind::InvalidInput, kind(&backslash));
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn prefix_is_cached() {
+        let first = PathAbs::new(r"C:\Windows").unwrap();
+        let cache = PREFIX_CACHE.get().expect("cache initialized");
+        let cached = lock_prefix_cache(cache).get(ffi::OsStr::new("C:")).cloned();
+        assert_eq!(Some(PathBuf::from(r"\\?\C:")), cached);
+
+        let second = PathAbs::new(r"C:\Windows").unwrap();
+        assert_eq!(first, second);
+    }
+}
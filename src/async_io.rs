@@ -0,0 +1,236 @@
+/* Copyright (c) 2018 Garrett Berg, vitiral@gmail.com
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+//! Async file handles built on `tokio::fs::File`, the counterparts of `FileRead`/`FileWrite`.
+//!
+//! These require the `async-tokio` feature.
+
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std_prelude::*;
+use tokio::fs::{File as TokioFile, OpenOptions};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+use super::{Error, PathAbs, PathFile, PathInfo, Result};
+
+/// Open `path` with `options`, annotating any error with the path.
+async fn open_file(path: PathAbs, options: &OpenOptions) -> Result<(PathFile, TokioFile)> {
+    let file = options
+        .open(&path)
+        .await
+        .map_err(|err| Error::new(err, "opening", path.clone().into()))?;
+    Ok((PathFile::new_unchecked(path), file))
+}
+
+/// Add the path to an `io::Error` returned by the `AsyncRead`/`AsyncWrite` impls.
+fn annotate(err: io::Error, action: &str, path: &PathFile) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("{} when {} {}", err, action, path.display()),
+    )
+}
+
+/// An async read-only file handle with `path()` attached and improved error messages.
+///
+/// This is the async version of [`FileRead`] and requires the `async-tokio` feature.
+///
+/// [`FileRead`]: struct.FileRead.html
+///
+/// # Examples
+/// ```rust
+/// # extern crate path_abs;
+/// # extern crate tempfile;
+/// # extern crate tokio;
+/// use path_abs::{AsyncFileRead, PathFile};
+///
+/// # async fn try_main() -> ::std::io::Result<()> {
+/// let example = "example.txt";
+/// # let tmp = tempfile::TempDir::new()?;
+/// # let example = &tmp.path().join(example);
+/// let file = PathFile::create(example)?;
+/// file.write_str("foo\nbar")?;
+///
+/// let mut read = AsyncFileRead::open(example).await?;
+/// assert_eq!("foo\nbar", read.read_string().await?);
+/// # Ok(()) }
+/// # fn main() {
+/// #     let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// #     rt.block_on(try_main()).unwrap()
+/// # }
+/// ```
+pub struct AsyncFileRead {
+    path: PathFile,
+    file: TokioFile,
+}
+
+impl AsyncFileRead {
+    /// Open the file as read-only.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<AsyncFileRead> {
+        AsyncFileRead::open_abs(PathAbs::new(path)?).await
+    }
+
+    /// Shortcut to open the file if the path is already absolute.
+    pub(crate) async fn open_abs<P: Into<PathAbs>>(path: P) -> Result<AsyncFileRead> {
+        let mut options = OpenOptions::new();
+        options.read(true);
+        let (path, file) = open_file(path.into(), &options).await?;
+        Ok(AsyncFileRead { path, file })
+    }
+
+    /// Get the path associated with the open file.
+    pub fn path(&self) -> &PathFile {
+        &self.path
+    }
+
+    /// Read what remains of the file to a `String`.
+    pub async fn read_string(&mut self) -> Result<String> {
+        let mut s = String::new();
+        self.file
+            .read_to_string(&mut s)
+            .await
+            .map_err(|err| Error::new(err, "reading", self.path.clone().into()))?;
+        Ok(s)
+    }
+}
+
+impl fmt::Debug for AsyncFileRead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncFileRead(")?;
+        self.path.fmt(f)?;
+        write!(f, ")")
+    }
+}
+
+impl AsyncRead for AsyncFileRead {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.file)
+            .poll_read(cx, buf)
+            .map_err(|err| annotate(err, "reading", &this.path))
+    }
+}
+
+/// An async write-only file handle with `path()` attached and improved error messages.
+///
+/// This is the async version of [`FileWrite`] and requires the `async-tokio` feature.
+///
+/// [`FileWrite`]: struct.FileWrite.html
+///
+/// # Examples
+/// ```rust
+/// # extern crate path_abs;
+/// # extern crate tempfile;
+/// # extern crate tokio;
+/// use path_abs::{AsyncFileWrite, PathFile};
+///
+/// # async fn try_main() -> ::std::io::Result<()> {
+/// let example = "example.txt";
+/// # let tmp = tempfile::TempDir::new()?;
+/// # let example = &tmp.path().join(example);
+/// let mut write = AsyncFileWrite::create(example).await?;
+/// write.write_str("foo\nbar").await?;
+/// write.flush().await?;
+///
+/// assert_eq!("foo\nbar", PathFile::new(example)?.read_string()?);
+/// # Ok(()) }
+/// # fn main() {
+/// #     let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// #     rt.block_on(try_main()).unwrap()
+/// # }
+/// ```
+pub struct AsyncFileWrite {
+    path: PathFile,
+    file: TokioFile,
+}
+
+impl AsyncFileWrite {
+    /// Open the file in write-only mode, truncating it first if it exists and creating it
+    /// otherwise.
+    pub async fn create<P: AsRef<Path>>(path: P) -> Result<AsyncFileWrite> {
+        let mut options = OpenOptions::new();
+        options.write(true).truncate(true).create(true);
+        AsyncFileWrite::open_abs(PathAbs::new(path)?, options).await
+    }
+
+    /// Open the file for appending, creating it if it doesn't exist.
+    pub async fn open_append<P: AsRef<Path>>(path: P) -> Result<AsyncFileWrite> {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        AsyncFileWrite::open_abs(PathAbs::new(path)?, options).await
+    }
+
+    /// Shortcut to open the file if the path is already absolute.
+    pub(crate) async fn open_abs<P: Into<PathAbs>>(
+        path: P,
+        options: OpenOptions,
+    ) -> Result<AsyncFileWrite> {
+        let (path, file) = open_file(path.into(), &options).await?;
+        Ok(AsyncFileWrite { path, file })
+    }
+
+    /// Get the path associated with the open file.
+    pub fn path(&self) -> &PathFile {
+        &self.path
+    }
+
+    /// Shortcut to `self.write_all(s.as_bytes())` with slightly improved error message.
+    pub async fn write_str(&mut self, s: &str) -> Result<()> {
+        self.file
+            .write_all(s.as_bytes())
+            .await
+            .map_err(|err| Error::new(err, "writing", self.path.clone().into()))
+    }
+
+    /// `tokio::fs::File::flush` but with the new error type.
+    pub async fn flush(&mut self) -> Result<()> {
+        self.file
+            .flush()
+            .await
+            .map_err(|err| Error::new(err, "flushing", self.path.clone().into()))
+    }
+}
+
+impl fmt::Debug for AsyncFileWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncFileWrite(")?;
+        self.path.fmt(f)?;
+        write!(f, ")")
+    }
+}
+
+impl AsyncWrite for AsyncFileWrite {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.file)
+            .poll_write(cx, buf)
+            .map_err(|err| annotate(err, "writing to", &this.path))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.file)
+            .poll_flush(cx)
+            .map_err(|err| annotate(err, "flushing", &this.path))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.file)
+            .poll_shutdown(cx)
+            .map_err(|err| annotate(err, "shutting down", &this.path))
+    }
+}
@@ -6,14 +6,20 @@
  * copied, modified, or distributed except according to those terms.
  */
 //! Paths to Directories and associated methods.
+use std::collections::BTreeMap;
+#[cfg(unix)]
+use std::collections::HashSet;
 use std::ffi;
 use std::fmt;
 use std::fs;
 use std::io;
+use std::path::Component;
+use std::time::SystemTime;
 use std_prelude::*;
 
+use super::file::symlink_file;
 use super::{Error, Result};
-use super::{PathAbs, PathInfo, PathOps, PathType};
+use super::{PathAbs, PathFile, PathInfo, PathOps, PathType, Relative};
 
 #[derive(Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
 /// A `PathAbs` that is guaranteed to be a directory, with associated methods.
@@ -49,6 +55,33 @@ impl PathDir {
         PathDir(PathAbs::new_unchecked(path))
     }
 
+    /// Instantiate a new `PathDir` if the path exists, returning `None` if it doesn't.
+    ///
+    /// Unlike [`new`], a missing path is not an error. An error is still returned if the path
+    /// exists but is not a directory, or if it could not be queried.
+    ///
+    /// [`new`]: #method.new
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// assert!(PathDir::try_new("src")?.is_some());
+    /// assert!(PathDir::try_new("does-not-exist")?.is_none());
+    /// assert!(PathDir::try_new("src/lib.rs").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn try_new<P: AsRef<Path>>(path: P) -> Result<Option<PathDir>> {
+        let abs = PathAbs::new(path)?;
+        match abs.metadata() {
+            Ok(_) => PathDir::try_from(abs).map(Some),
+            Err(ref err) if err.io_error().kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Returns the current working directory from the `env` as a `PathDir`.
     ///
     /// # Examples
@@ -139,6 +172,10 @@ impl PathDir {
     /// Instantiate a new `PathDir` to a directory, recursively recreating it and all of its parent
     /// components if they are missing.
     ///
+    /// If this fails, the message of the error names the deepest existing ancestor and the
+    /// component which could not be created (e.g. because it is a file, or permission was denied).
+    /// The error kind, action (`"creating-all"`) and path are the same as for any other failure.
+    ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
@@ -157,27 +194,380 @@ impl PathDir {
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
     pub fn create_all<P: AsRef<Path>>(path: P) -> Result<PathDir> {
-        fs::create_dir_all(&path)
-            .map_err(|err| Error::new(err, "creating-all", path.as_ref().to_path_buf().into()))?;
+        fs::create_dir_all(&path).map_err(|err| {
+            Error::new(
+                locate_create_all_failure(path.as_ref(), err),
+                "creating-all",
+                path.as_ref().to_path_buf().into(),
+            )
+        })?;
+        PathDir::new(path)
+    }
+
+    /// Create the directory with the given permission `mode`, then change its owner to `uid` and
+    /// its group to `gid`.
+    ///
+    /// This is the usual privileged-setup operation for tools running as root. Unlike [`create`],
+    /// this returns an error if the directory already exists. As with `mkdir`, the `mode` is
+    /// masked by the process's umask.
+    ///
+    /// This is only available on Unix.
+    ///
+    /// [`create`]: #method.create
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::os::unix::fs::MetadataExt;
+    /// use path_abs::{PathDir, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// // Without root privileges we can only give the directory to ourselves.
+    /// let parent = tmp.path().metadata()?;
+    /// let dir = PathDir::create_owned(example, parent.uid(), parent.gid(), 0o750)?;
+    ///
+    /// let metadata = dir.metadata()?;
+    /// assert_eq!(parent.uid(), metadata.uid());
+    /// assert_eq!(0, metadata.mode() & 0o027);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(unix)]
+    pub fn create_owned<P: AsRef<Path>>(path: P, uid: u32, gid: u32, mode: u32) -> Result<PathDir> {
+        use std::os::unix::fs::{chown, DirBuilderExt};
+
+        let path = path.as_ref();
+        fs::DirBuilder::new()
+            .mode(mode)
+            .create(path)
+            .map_err(|err| Error::new(err, "creating", path.to_path_buf().into()))?;
+        chown(path, Some(uid), Some(gid))
+            .map_err(|err| Error::new(err, "changing owner of", path.to_path_buf().into()))?;
         PathDir::new(path)
     }
 
+    /// Instantiate a new `PathDir`, recursively creating the directory if it doesn't exist.
+    ///
+    /// Unlike [`create_all`], this first checks whether the path exists and returns
+    /// `io::ErrorKind::InvalidInput` if it is not a directory.
+    ///
+    /// [`create_all`]: struct.PathDir.html#method.create_all
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example/long/path";
+    /// let example_file = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_file = &tmp.path().join(example_file);
+    ///
+    /// let dir = PathDir::ensure(example)?;
+    /// assert_eq!(dir, PathDir::ensure(example)?);
+    ///
+    /// PathFile::create(example_file)?;
+    /// assert!(PathDir::ensure(example_file).is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn ensure<P: AsRef<Path>>(path: P) -> Result<PathDir> {
+        let abs = PathAbs::new(path)?;
+        if abs.exists() {
+            PathDir::try_from(abs)
+        } else {
+            PathDir::create_all(abs)
+        }
+    }
+
+    /// Recursively create a directory under this one, validating that `relative` cannot escape
+    /// it.
+    ///
+    /// `relative` is validated as a [`Relative`] path, so it may not be absolute or use `..` to
+    /// go above `self`.
+    ///
+    /// [`Relative`]: struct.Relative.html
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let dir = PathDir::create(example)?;
+    ///
+    /// let nested = dir.create_under("foo/bar")?;
+    /// assert_eq!(PathDir::new(dir.concat("foo/bar")?)?, nested);
+    ///
+    /// assert!(dir.create_under("../escaped").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn create_under<P: AsRef<Path>>(&self, relative: P) -> Result<PathDir> {
+        let relative = Relative::new(relative)?;
+        PathDir::create_all(self.concat(relative)?)
+    }
+
+    /// Create a file at `rel` inside this directory, truncating it if it already exists.
+    ///
+    /// This is [`PathFile::create`] on the concatenated path. Returns `io::ErrorKind::InvalidInput`
+    /// if `rel` is absolute.
+    ///
+    /// [`PathFile::create`]: struct.PathFile.html#method.create
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let dir = PathDir::create(example)?;
+    ///
+    /// let sub = dir.create_dir("sub")?;
+    /// let file = sub.create_file("foo.txt")?;
+    /// assert!(file.is_file());
+    /// assert_eq!(sub, file.parent_dir());
+    ///
+    /// assert!(dir.create_file("/etc/foo.txt").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn create_file<P: AsRef<Path>>(&self, rel: P) -> Result<PathFile> {
+        PathFile::create(self.concat_relative(rel.as_ref())?)
+    }
+
+    /// Create a directory at `rel` inside this directory, doing nothing if it already exists.
+    ///
+    /// This is [`PathDir::create`] on the concatenated path, so the parent of `rel` must already
+    /// exist. Returns `io::ErrorKind::InvalidInput` if `rel` is absolute. See [`create_file`] for
+    /// an example.
+    ///
+    /// [`PathDir::create`]: #method.create
+    /// [`create_file`]: #method.create_file
+    pub fn create_dir<P: AsRef<Path>>(&self, rel: P) -> Result<PathDir> {
+        PathDir::create(self.concat_relative(rel.as_ref())?)
+    }
+
+    /// Create a new, empty file with a unique name inside this directory.
+    ///
+    /// The name is `prefix`, a random component and `suffix`. The file is created with
+    /// `create_new`, retrying with another name if it already exists, so the returned file is
+    /// guaranteed to be new even if other processes are doing the same. This makes it a good
+    /// staging file for writing and then atomically renaming into place.
+    ///
+    /// The file is not removed automatically. Returns `io::ErrorKind::InvalidInput` if `prefix` or
+    /// `suffix` would make the name more than one path component.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    /// let dir = PathDir::create(example)?;
+    ///
+    /// let staging = dir.temp_child(".config-", ".tmp")?;
+    /// assert_eq!(dir, staging.parent_dir());
+    /// let name = staging.file_name().unwrap().to_str().unwrap();
+    /// assert!(name.starts_with(".config-") && name.ends_with(".tmp"));
+    ///
+    /// staging.write_str("new = true")?;
+    /// staging.rename(dir.join("config.toml"))?;
+    ///
+    /// assert_ne!(dir.temp_child("", "")?, dir.temp_child("", "")?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn temp_child(&self, prefix: &str, suffix: &str) -> Result<PathFile> {
+        // Enough attempts that only something other than name collisions can exhaust them.
+        const ATTEMPTS: usize = 128;
+
+        let template = format!("{}XXXX{}", prefix, suffix);
+        let mut components = Path::new(&template).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => (),
+            _ => {
+                return Err(Error::new(
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "prefix and suffix must not contain path separators",
+                    ),
+                    "creating temporary file in",
+                    self.clone().into(),
+                ));
+            }
+        }
+
+        for _ in 0..ATTEMPTS {
+            let name = format!("{}{:016x}{}", prefix, random_u64(), suffix);
+            let path = self.as_path().join(name);
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(PathFile::new_unchecked(path)),
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(Error::new(err, "creating", path.into())),
+            }
+        }
+        Err(Error::new(
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "could not find an unused name",
+            ),
+            "creating temporary file in",
+            self.clone().into(),
+        ))
+    }
+
+    /// `concat`, but rejecting absolute paths instead of treating them as relative.
+    fn concat_relative(&self, rel: &Path) -> Result<PathAbs> {
+        if rel.has_root() {
+            return Err(Error::new(
+                io::Error::new(io::ErrorKind::InvalidInput, "path is absolute"),
+                "creating",
+                rel.to_path_buf().into(),
+            ));
+        }
+        self.concat(rel)
+    }
+
     /// Join a path onto the `PathDir`, expecting it to exist. Returns the resulting `PathType`.
     ///
+    /// If the joined path doesn't exist the error is `io::ErrorKind::NotFound` with the action
+    /// `"joining (path does not exist)"`. Use [`get`] if the path may be absent.
+    ///
+    /// [`get`]: #method.get
+    ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
+    /// use std::io;
     /// use path_abs::{PathDir, PathFile, PathInfo};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
     /// let src = PathDir::new("src")?;
     /// let lib = src.join_abs("lib.rs")?.unwrap_file();
     /// assert!(lib.is_file());
+    ///
+    /// let err = src.join_abs("does-not-exist.rs").unwrap_err();
+    /// assert_eq!(io::ErrorKind::NotFound, err.io_error().kind());
+    /// assert_eq!("joining (path does not exist)", err.action());
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
     pub fn join_abs<P: AsRef<Path>>(&self, path: P) -> Result<PathType> {
         let joined = self.concat(path.as_ref())?;
-        PathType::new(joined)
+        PathType::try_from(joined.clone()).map_err(|err| {
+            let io_err = err.io_error();
+            if io_err.kind() == io::ErrorKind::NotFound {
+                Error::new(
+                    io::Error::new(io_err.kind(), io_err.to_string()),
+                    "joining (path does not exist)",
+                    joined.into(),
+                )
+            } else {
+                err
+            }
+        })
+    }
+
+    /// Look up a child of the `PathDir` that may not exist.
+    ///
+    /// Returns `Ok(None)` if the joined path doesn't exist and `Ok(Some(ty))` if it does. Other
+    /// IO errors, such as permission denied, are still returned as an `Err`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathDir, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    /// let lib = src.get("lib.rs")?.expect("lib.rs exists").unwrap_file();
+    /// assert!(lib.is_file());
+    ///
+    /// assert!(src.get("does-not-exist.rs")?.is_none());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn get<P: AsRef<Path>>(&self, name: P) -> Result<Option<PathType>> {
+        let joined = self.concat(name.as_ref())?;
+        match PathType::try_from(joined) {
+            Ok(ty) => Ok(Some(ty)),
+            Err(ref err) if err.io_error().kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolve an untrusted relative path to an existing entry, guaranteeing that it is inside
+    /// the directory.
+    ///
+    /// `rel` is first checked like [`PathAbs::new_under`], rejecting absolute paths and `..`
+    /// traversal. The joined path must exist and symlinks are then resolved: if the canonicalized
+    /// result is not inside the canonicalized directory (i.e. a symlink points outside of it) the
+    /// error is `io::ErrorKind::PermissionDenied`. The returned `PathType` is the canonicalized
+    /// path, so it is what was actually checked.
+    ///
+    /// [`PathAbs::new_under`]: struct.PathAbs.html#method.new_under
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io;
+    /// use path_abs::{PathDir, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("www");
+    /// # let secret = &tmp.path().join("secret.txt");
+    /// let www = PathDir::create(example)?;
+    /// www.create_file("index.html")?;
+    ///
+    /// assert!(www.join_under("static/../index.html")?.is_file());
+    ///
+    /// let err = www.join_under("../secret.txt").unwrap_err();
+    /// assert_eq!(io::ErrorKind::PermissionDenied, err.io_error().kind());
+    ///
+    /// // Symlinks pointing out of the directory are caught as well.
+    /// # #[cfg(unix)] {
+    /// let secret = path_abs::PathFile::create(secret)?;
+    /// secret.symlink(www.concat("link.txt")?)?;
+    /// let err = www.join_under("link.txt").unwrap_err();
+    /// assert_eq!(io::ErrorKind::PermissionDenied, err.io_error().kind());
+    /// # }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn join_under<P: AsRef<Path>>(&self, rel: P) -> Result<PathType> {
+        let joined = PathAbs::new_under(self, rel)?;
+        let canonical = joined.canonicalize()?;
+        if !canonical
+            .as_path()
+            .starts_with(self.canonicalize()?.as_path())
+        {
+            return Err(Error::new(
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "path escapes the directory through a symlink",
+                ),
+                &format!("resolving under {}", self.as_path().display()),
+                joined.into(),
+            ));
+        }
+        PathType::try_from(canonical)
     }
 
     /// List the contents of the directory, returning an iterator of `PathType`s.
@@ -214,143 +604,978 @@ impl PathDir {
             .map_err(|err| Error::new(err, "reading dir", self.clone().into()))?;
         Ok(ListDir {
             dir: self.clone(),
-            fsread: fsread,
+            fsread: Some(fsread),
+            err: None,
         })
     }
 
-    /// Remove (delete) the _empty_ directory from the filesystem, consuming self.
+    /// List the contents of the directory as raw `fs::DirEntry`s.
+    ///
+    /// This is an escape hatch for when the entry carries information that `PathType` discards,
+    /// such as the inode number on Unix. Errors while iterating are annotated with the path of
+    /// the directory.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    /// let mut names = Vec::new();
+    /// for entry in src.list_entries()? {
+    ///     names.push(entry?.file_name());
+    /// }
+    /// assert!(names.iter().any(|name| name == "lib.rs"));
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn list_entries(&self) -> Result<impl Iterator<Item = Result<fs::DirEntry>>> {
+        let fsread = fs::read_dir(self)
+            .map_err(|err| Error::new(err, "reading dir", self.clone().into()))?;
+        let dir = self.clone();
+        Ok(fsread.map(move |entry| {
+            entry.map_err(|err| Error::new(err, "iterating over", dir.clone().into()))
+        }))
+    }
+
+    /// Count the immediate entries of the directory.
+    ///
+    /// This is cheaper than counting [`list`], which has to `stat` every entry to build its
+    /// `PathType`.
+    ///
+    /// [`list`]: #method.list
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use std::path::Path;
     /// use path_abs::PathDir;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = Path::new("example/long/path");
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
-    ///
-    /// let dir = PathDir::create_all(example)?;
-    /// let parent = dir.parent_dir().unwrap();
+    /// # let example = &tmp.path().join("example");
+    /// let dir = PathDir::create(example)?;
+    /// assert_eq!(0, dir.entries_count()?);
     ///
-    /// assert!(example.exists());
-    /// dir.remove()?;
-    /// // assert!(dir.exists());  <--- COMPILE ERROR
-    /// assert!(!example.exists());
-    /// parent.remove()?;
+    /// dir.create_file("foo.txt")?;
+    /// dir.create_dir("bar")?.create_file("baz.txt")?;
+    /// assert_eq!(2, dir.entries_count()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn remove(self) -> Result<()> {
-        fs::remove_dir(&self).map_err(|err| Error::new(err, "removing", self.into()))
+    pub fn entries_count(&self) -> Result<usize> {
+        let mut count = 0;
+        for entry in self.list_entries()? {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
     }
 
-    /// Remove (delete) the directory, after recursively removing its contents. Use carefully!
+    /// Collect the file names of the immediate entries of the directory, in no particular order.
+    ///
+    /// Unlike [`list`], entries are not `stat`ed, which is much faster for large directories
+    /// when only some of the entries are needed (e.g. filtering by extension first).
+    ///
+    /// [`list`]: #method.list
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use std::path::Path;
     /// use path_abs::PathDir;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = Path::new("example/long/path");
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
-    ///
-    /// let dir = PathDir::create_all(example)?;
-    /// let parent = dir.parent_dir().unwrap();
+    /// # let example = &tmp.path().join("example");
+    /// let dir = PathDir::create(example)?;
+    /// dir.create_file("foo.txt")?;
+    /// dir.create_dir("bar")?;
     ///
-    /// assert!(example.exists());
-    /// parent.remove_all()?;
-    /// assert!(!example.exists());
+    /// let mut names = dir.read_dir_names()?;
+    /// names.sort();
+    /// assert_eq!(vec!["bar", "foo.txt"], names);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn remove_all(self) -> Result<()> {
-        fs::remove_dir_all(&self).map_err(|err| Error::new(err, "removing-all", self.into()))
+    pub fn read_dir_names(&self) -> Result<Vec<ffi::OsString>> {
+        self.list_entries()?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect()
     }
 
-    /// Creates a new symbolic link on the filesystem to the dst.
+    /// List the contents of the directory with directories first, each group sorted by path.
     ///
-    /// This handles platform specific behavior correctly.
+    /// This is the ordering most file browsers use.
     ///
     /// # Examples
-    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use path_abs::{PathDir, PathFile, PathOps};
-    /// use std::path::Path;
+    /// use path_abs::{PathDir, PathFile, PathOps, PathType};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
     /// let example = "example";
-    /// let example_sym = "example_sym";
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
-    /// # let example_sym = &tmp.path().join(example_sym);
-    /// let dir = PathDir::create(example)?;
-    /// let file = PathFile::create(dir.concat("example.txt")?)?;
-    ///
-    /// let dir_sym = dir.symlink(example_sym)?;
-    ///
-    /// // They have a different "absolute path"
-    /// assert_ne!(dir, dir_sym);
+    /// # let example = &tmp.path().join("example");
     ///
-    /// // But they can be canonicalized to the same file.
-    /// let dir_can = dir_sym.canonicalize()?;
-    /// assert_eq!(dir, dir_can);
+    /// let example_dir = PathDir::create(example)?;
+    /// let a_file = PathFile::create(example_dir.concat("a.txt")?)?;
+    /// let b_dir = PathDir::create(example_dir.concat("b")?)?;
+    /// let c_file = PathFile::create(example_dir.concat("c.txt")?)?;
+    /// let d_dir = PathDir::create(example_dir.concat("d")?)?;
     ///
+    /// let expected = vec![
+    ///     PathType::Dir(b_dir),
+    ///     PathType::Dir(d_dir),
+    ///     PathType::File(a_file),
+    ///     PathType::File(c_file),
+    /// ];
+    /// assert_eq!(expected, example_dir.list_sorted_dirs_first()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn symlink<P: AsRef<Path>>(&self, dst: P) -> Result<PathDir> {
-        symlink_dir(&self, &dst).map_err(|err| {
-            Error::new(
-                err,
-                &format!("linking from {} to", dst.as_ref().display()),
-                self.clone().into(),
-            )
-        })?;
-        PathDir::new(dst)
-    }
-
-    /// Return a reference to a basic `std::path::Path`
-    pub fn as_path(&self) -> &Path {
-        self.as_ref()
+    pub fn list_sorted_dirs_first(&self) -> Result<Vec<PathType>> {
+        let mut entries = self.list()?.collect::<Result<Vec<_>>>()?;
+        entries.sort_by(|a, b| {
+            b.is_dir()
+                .cmp(&a.is_dir())
+                .then_with(|| a.as_path().cmp(b.as_path()))
+        });
+        Ok(entries)
     }
 
-    /// Returns the canonical form of the path with all intermediate components normalized and
-    /// symbolic links resolved.
+    /// List the contents of the directory like [`list`], resolving the type of each entry in
+    /// parallel.
+    ///
+    /// Resolving an entry's type requires a `stat` call, so for large directories this can be
+    /// much faster than [`list`]. The entries are returned in the same order as [`list`] and the
+    /// first error encountered is returned.
+    ///
+    /// > This requires the `rayon` feature, which needs Rust 1.80 or newer.
+    ///
+    /// [`list`]: struct.PathDir.html#method.list
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    ///
+    /// let example_dir = PathDir::create(example)?;
+    /// for i in 0..10 {
+    ///     PathFile::create(example_dir.concat(format!("{}.txt", i))?)?;
+    /// }
+    ///
+    /// let entries = example_dir.par_list()?;
+    /// assert_eq!(entries.len(), 10);
+    /// assert!(entries.iter().all(|e| e.is_file()));
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_list(&self) -> Result<Vec<PathType>> {
+        use rayon::prelude::*;
+
+        let paths = fs::read_dir(self)
+            .map_err(|err| Error::new(err, "reading dir", self.clone().into()))?
+            .map(|entry| {
+                entry
+                    .map(|e| e.path())
+                    .map_err(|err| Error::new(err, "iterating over", self.clone().into()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        paths.into_par_iter().map(PathType::new).collect()
+    }
+
+    /// Call `f` with every entry of the directory, stopping at the first error.
+    ///
+    /// This is the same as iterating over [`list`], but can be more convenient when the callback
+    /// needs to mutate captured state. Errors from `f` are returned as-is.
+    ///
+    /// [`list`]: struct.PathDir.html#method.list
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    ///
+    /// let example_dir = PathDir::create(example)?;
+    /// PathDir::create(example_dir.concat("foo")?)?;
+    /// PathFile::create(example_dir.concat("bar.txt")?)?;
+    /// PathFile::create(example_dir.concat("baz.txt")?)?;
+    ///
+    /// let mut files = 0;
+    /// example_dir.for_each_entry(|entry| {
+    ///     if let PathType::File(_) = entry {
+    ///         files += 1;
+    ///     }
+    ///     Ok(())
+    /// })?;
+    /// assert_eq!(files, 2);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn for_each_entry<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(PathType) -> Result<()>,
+    {
+        for entry in self.list()? {
+            f(entry?)?;
+        }
+        Ok(())
+    }
+
+    /// Rename the directory, replacing `to` if it is an empty directory.
+    ///
+    /// This will not work if the new name is on a different mount point.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// let example_bk = "example.bk";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_bk = &tmp.path().join(example_bk);
+    /// let dir = PathDir::create(example)?;
+    /// PathFile::create(dir.concat("foo.txt")?)?;
+    ///
+    /// let dir_bk = dir.clone().rename(example_bk)?;
+    /// assert!(!dir.exists());
+    /// assert!(dir_bk.concat("foo.txt")?.is_file());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn rename<P: AsRef<Path>>(self, to: P) -> Result<PathDir> {
+        fs::rename(&self, &to).map_err(|err| {
+            Error::new(
+                err,
+                &format!("renaming to {} from", to.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        PathDir::new(to)
+    }
+
+    /// Get the last modification time of the directory.
+    ///
+    /// A directory's modification time changes when entries are added to or removed from it,
+    /// which makes it useful for cache invalidation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let dir = PathDir::create(example)?;
+    /// let before = dir.modified()?;
+    ///
+    /// PathFile::create(dir.concat("foo.txt")?)?;
+    /// assert!(dir.modified()? >= before);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn modified(&self) -> Result<SystemTime> {
+        self.metadata()?
+            .modified()
+            .map_err(|err| Error::new(err, "getting modified time of", self.clone().into()))
+    }
+
+    /// Get the creation time of the directory.
+    ///
+    /// Not all platforms and filesystems record this, in which case an error is returned.
+    pub fn created(&self) -> Result<SystemTime> {
+        self.metadata()?
+            .created()
+            .map_err(|err| Error::new(err, "getting creation time of", self.clone().into()))
+    }
+
+    /// Remove (delete) the _empty_ directory from the filesystem, consuming self.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::path::Path;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = Path::new("example/long/path");
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    ///
+    /// let dir = PathDir::create_all(example)?;
+    /// let parent = dir.parent_dir().unwrap();
+    ///
+    /// assert!(example.exists());
+    /// dir.remove()?;
+    /// // assert!(dir.exists());  <--- COMPILE ERROR
+    /// assert!(!example.exists());
+    /// parent.remove()?;
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn remove(self) -> Result<()> {
+        fs::remove_dir(&self).map_err(|err| Error::new(err, "removing", self.into()))
+    }
+
+    /// Remove (delete) the directory, after recursively removing its contents. Use carefully!
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::path::Path;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = Path::new("example/long/path");
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    ///
+    /// let dir = PathDir::create_all(example)?;
+    /// let parent = dir.parent_dir().unwrap();
+    ///
+    /// assert!(example.exists());
+    /// parent.remove_all()?;
+    /// assert!(!example.exists());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn remove_all(self) -> Result<()> {
+        fs::remove_dir_all(&self).map_err(|err| Error::new(err, "removing-all", self.into()))
+    }
+
+    /// Remove the directory by moving it, with all of its contents, into `backup_dir`.
+    ///
+    /// This is like [`remove_all`] but keeps a way to undo the removal. The directory keeps its
+    /// name inside `backup_dir`, and an `io::ErrorKind::AlreadyExists` error is returned if that
+    /// path is already taken. If both directories are on the same filesystem the directory is
+    /// renamed, otherwise it is copied (see [`copy_preserve_symlinks`]) and then removed.
+    ///
+    /// [`remove_all`]: #method.remove_all
+    /// [`copy_preserve_symlinks`]: #method.copy_preserve_symlinks
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    /// # let backups = &tmp.path().join("backups");
+    /// let dir = PathDir::create(example)?;
+    /// dir.create_file("foo.txt")?.write_str("foo")?;
+    /// let backups = PathDir::create(backups)?;
+    ///
+    /// dir.clone().remove_all_backup(&backups)?;
+    /// assert!(!dir.exists());
+    ///
+    /// let backup = PathFile::new(backups.concat("example/foo.txt")?)?;
+    /// assert_eq!("foo", backup.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn remove_all_backup(self, backup_dir: &PathDir) -> Result<()> {
+        let err = |kind, msg, path: &PathDir| {
+            Error::new(
+                io::Error::new(kind, msg),
+                &format!("backing up to {} from", backup_dir.display()),
+                path.clone().into(),
+            )
+        };
+
+        let name = match self.as_path().file_name() {
+            Some(name) => name,
+            None => {
+                return Err(err(
+                    io::ErrorKind::InvalidInput,
+                    "directory has no name",
+                    &self,
+                ))
+            }
+        };
+        let backup = backup_dir.concat(name)?;
+        if PathInfo::symlink_metadata(&backup).is_ok() {
+            return Err(err(
+                io::ErrorKind::AlreadyExists,
+                "backup already exists",
+                &self,
+            ));
+        }
+
+        if self.same_filesystem_as(backup_dir)? {
+            self.rename(backup)?;
+        } else {
+            self.copy_preserve_symlinks(backup)?;
+            self.remove_all()?;
+        }
+        Ok(())
+    }
+
+    /// Creates a new symbolic link on the filesystem to the dst.
+    ///
+    /// This handles platform specific behavior correctly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps};
+    /// use std::path::Path;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// let example_sym = "example_sym";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_sym = &tmp.path().join(example_sym);
+    /// let dir = PathDir::create(example)?;
+    /// let file = PathFile::create(dir.concat("example.txt")?)?;
+    ///
+    /// let dir_sym = dir.symlink(example_sym)?;
+    ///
+    /// // They have a different "absolute path"
+    /// assert_ne!(dir, dir_sym);
+    ///
+    /// // But they can be canonicalized to the same file.
+    /// let dir_can = dir_sym.canonicalize()?;
+    /// assert_eq!(dir, dir_can);
+    ///
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn symlink<P: AsRef<Path>>(&self, dst: P) -> Result<PathDir> {
+        symlink_dir(&self, &dst).map_err(|err| {
+            Error::new(
+                err,
+                &format!("linking from {} to", dst.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        PathDir::new(dst)
+    }
+
+    /// Recursively copy the directory and all of its contents to another location, creating
+    /// `path` if it doesn't exist.
+    ///
+    /// Symlinks are followed: the contents they point to are copied. A symlink pointing to one of
+    /// its own ancestors would expand forever, so it is detected and returns
+    /// `io::ErrorKind::InvalidInput`. Use [`copy_preserve_symlinks`] to recreate the links
+    /// instead.
+    ///
+    /// [`copy_preserve_symlinks`]: struct.PathDir.html#method.copy_preserve_symlinks
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// let example_bk = "example_bk";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_bk = &tmp.path().join(example_bk);
+    /// let dir = PathDir::create(example)?;
+    /// let foo = PathDir::create(dir.concat("foo")?)?;
+    /// let file = PathFile::create(foo.concat("example.txt")?)?;
+    /// file.write_str("some contents")?;
+    ///
+    /// let dir_bk = dir.copy(example_bk)?;
+    /// let file_bk = PathFile::new(dir_bk.concat("foo/example.txt")?)?;
+    /// assert_eq!("some contents", file_bk.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn copy<P: AsRef<Path>>(&self, path: P) -> Result<PathDir> {
+        copy_tree(self, path.as_ref(), false)?;
+        PathDir::new(path)
+    }
+
+    /// Recursively copy the directory and all of its contents to another location, recreating
+    /// symlinks as symlinks instead of copying what they point to.
+    ///
+    /// Symlinks are never followed, so symlink loops cannot cause infinite expansion. Link
+    /// targets are copied verbatim, so relative targets are resolved relative to the new
+    /// location (like `cp -rP`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathInfo, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// let example_bk = "example_bk";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_bk = &tmp.path().join(example_bk);
+    /// let dir = PathDir::create(example)?;
+    /// // A symlink pointing to its own parent
+    /// dir.symlink(dir.concat("loop")?)?;
+    ///
+    /// let dir_bk = dir.copy_preserve_symlinks(example_bk)?;
+    /// let ty = dir_bk.concat("loop")?.symlink_metadata()?.file_type();
+    /// assert!(ty.is_symlink());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn copy_preserve_symlinks<P: AsRef<Path>>(&self, path: P) -> Result<PathDir> {
+        copy_tree(self, path.as_ref(), true)?;
+        PathDir::new(path)
+    }
+
+    /// Return a reference to a basic `std::path::Path`
+    pub fn as_path(&self) -> &Path {
+        self.as_ref()
+    }
+
+    /// Return the path as a `&str` if it is valid UTF-8, without allocating.
+    ///
+    /// See [`PathAbs::as_str`]
+    ///
+    /// [`PathAbs::as_str`]: struct.PathAbs.html#method.as_str
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+
+    /// Returns the canonical form of the path with all intermediate components normalized and
+    /// symbolic links resolved.
     ///
     /// See [`PathAbs::canonicalize`]
     ///
-    /// [`PathAbs::canonicalize`]: struct.PathAbs.html#method.canonicalize
-    pub fn canonicalize(&self) -> Result<PathDir> {
-        Ok(PathDir(self.0.canonicalize()?))
+    /// [`PathAbs::canonicalize`]: struct.PathAbs.html#method.canonicalize
+    pub fn canonicalize(&self) -> Result<PathDir> {
+        Ok(PathDir(self.0.canonicalize()?))
+    }
+
+    /// Get the parent directory of this directory as a `PathDir`.
+    ///
+    /// > This does not make aditional syscalls, as the parent by definition must be a directory
+    /// > and exist.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    /// let proj = src.parent_dir().unwrap();
+    /// assert_eq!(PathDir::new("src/..")?, proj);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn parent_dir(&self) -> Option<PathDir> {
+        match self.parent() {
+            Ok(path) => Some(PathDir(PathAbs(Arc::new(path.to_path_buf())))),
+            Err(_) => None,
+        }
+    }
+
+    /// Search this directory and each of its ancestors for an entry called `name`, returning the
+    /// first one found.
+    ///
+    /// This is how tools commonly locate their project root, e.g. by looking for `Cargo.toml` or
+    /// `.git`. The search stops at the filesystem root.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathDir, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let src = PathDir::new("src")?;
+    /// match src.find_up("Cargo.toml") {
+    ///     Some(PathType::File(manifest)) => assert_eq!(manifest.parent_dir(), src.parent_dir().unwrap()),
+    ///     _ => panic!("Cargo.toml not found"),
+    /// }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn find_up<P: AsRef<Path>>(&self, name: P) -> Option<PathType> {
+        let name = name.as_ref();
+        self.0
+            .ancestors_abs()
+            .find_map(|dir| PathType::new(dir.as_path().join(name)).ok())
+    }
+
+    /// Recursively walk the contents of the directory, returning an iterator of `PathType`s.
+    ///
+    /// The walk is depth-first and each directory is yielded before its contents. The directory
+    /// itself is not yielded, and symlinks to directories are yielded but not descended into
+    /// unless [`WalkDir::follow_symlinks`] is enabled.
+    ///
+    /// [`WalkDir::follow_symlinks`]: struct.WalkDir.html#method.follow_symlinks
+    ///
+    /// If a directory cannot be read it is still yielded, followed by the error, and the walk
+    /// continues with the next entry.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::collections::HashSet;
+    /// use path_abs::{PathDir, PathFile, PathOps, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    ///
+    /// let example_dir = PathDir::create(example)?;
+    /// let foo_dir = PathDir::create(example_dir.concat("foo")?)?;
+    /// let bar_file = PathFile::create(foo_dir.concat("bar.txt")?)?;
+    ///
+    /// let mut result = HashSet::new();
+    /// for p in example_dir.walk() {
+    ///     result.insert(p?);
+    /// }
+    ///
+    /// let mut expected = HashSet::new();
+    /// expected.insert(PathType::Dir(foo_dir));
+    /// expected.insert(PathType::File(bar_file));
+    ///
+    /// assert_eq!(expected, result);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn walk(&self) -> WalkDir {
+        WalkDir {
+            root: Some(self.clone()),
+            stack: Vec::new(),
+            max_depth: None,
+            pending: None,
+            #[cfg(unix)]
+            visited: None,
+            followed: None,
+        }
+    }
+
+    /// Recursively walk the directory like [`walk`], also yielding each entry's path relative to
+    /// this directory.
+    ///
+    /// [`walk`]: struct.PathDir.html#method.walk
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::path::PathBuf;
+    /// use path_abs::{PathDir, PathFile, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    ///
+    /// let example_dir = PathDir::create(example)?;
+    /// PathDir::create(example_dir.concat("foo")?)?;
+    /// PathFile::create(example_dir.concat("foo/bar.txt")?)?;
+    ///
+    /// let mut manifest = Vec::new();
+    /// for entry in example_dir.walk_relative() {
+    ///     let (relative, _) = entry?;
+    ///     manifest.push(relative);
+    /// }
+    /// manifest.sort();
+    ///
+    /// assert_eq!(manifest, [PathBuf::from("foo"), PathBuf::from("foo/bar.txt")]);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn walk_relative(&self) -> impl Iterator<Item = Result<(PathBuf, PathType)>> {
+        let root = self.clone();
+        self.walk().map(move |entry| {
+            let entry = entry?;
+            let relative = entry
+                .as_path()
+                .strip_prefix(&root)
+                .expect("walked entries are inside the root")
+                .to_path_buf();
+            Ok((relative, entry))
+        })
+    }
+
+    /// Compare this directory tree against `other`.
+    ///
+    /// Both trees are walked (see [`walk`]) and entries are matched by their path relative to
+    /// the root of each tree. An entry is differing if it is a file in one tree and a directory
+    /// in the other, or if both are files without the [same contents]. All paths in the returned
+    /// [`DirDiff`] are relative and sorted.
+    ///
+    /// Entries which can't be resolved, such as dangling symlinks or directories which can't be
+    /// read, don't fail the diff: they are differing if they exist in both trees, since their
+    /// contents can't be compared, and are otherwise reported as only being in one tree.
+    ///
+    /// [`walk`]: struct.PathDir.html#method.walk
+    /// [same contents]: struct.PathFile.html#method.same_contents
+    /// [`DirDiff`]: struct.DirDiff.html
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::path::PathBuf;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let expected = &tmp.path().join("expected");
+    /// # let actual = &tmp.path().join("actual");
+    /// let expected = PathDir::create(expected)?;
+    /// expected.create_file("same.txt")?.write_str("same")?;
+    /// expected.create_file("changed.txt")?.write_str("old")?;
+    /// expected.create_file("removed.txt")?;
+    ///
+    /// let actual = PathDir::create(actual)?;
+    /// actual.create_file("same.txt")?.write_str("same")?;
+    /// actual.create_file("changed.txt")?.write_str("new")?;
+    /// actual.create_dir("added")?;
+    ///
+    /// let diff = expected.diff(&actual)?;
+    /// assert_eq!(diff.only_in_self, [PathBuf::from("removed.txt")]);
+    /// assert_eq!(diff.only_in_other, [PathBuf::from("added")]);
+    /// assert_eq!(diff.differing, [PathBuf::from("changed.txt")]);
+    ///
+    /// assert!(expected.diff(&expected)?.is_empty());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn diff(&self, other: &PathDir) -> Result<DirDiff> {
+        let ours = walk_lenient(self, self.walk())?;
+        let mut theirs = walk_lenient(other, other.walk())?;
+
+        let mut diff = DirDiff::default();
+        for (relative, ours) in ours {
+            match theirs.remove(&relative) {
+                None => diff.only_in_self.push(relative),
+                Some(theirs) => {
+                    let same = match (&ours, &theirs) {
+                        (Some(PathType::File(a)), Some(PathType::File(b))) => a.same_contents(b)?,
+                        (Some(PathType::Dir(_)), Some(PathType::Dir(_))) => true,
+                        _ => false,
+                    };
+                    if !same {
+                        diff.differing.push(relative);
+                    }
+                }
+            }
+        }
+        diff.only_in_other.extend(theirs.into_keys());
+        Ok(diff)
+    }
+
+    /// Compute a SHA-256 fingerprint of the directory tree's contents.
+    ///
+    /// The tree is walked (see [`walk`]) and, in sorted order, each entry's relative path and
+    /// type are hashed along with the size and contents of each file. The result only changes
+    /// when an entry is added, removed, renamed or modified, so it is stable across machines
+    /// and suitable as a cache key. Metadata such as timestamps and permissions is ignored.
+    ///
+    /// Symlinks are not followed: the path they point to is hashed instead of what it points at,
+    /// so dangling symlinks are fine. A directory which can't be read returns an error.
+    ///
+    /// > This requires the `digest` feature.
+    ///
+    /// [`walk`]: struct.PathDir.html#method.walk
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathDir;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    /// # let example_bk = &tmp.path().join("example_bk");
+    /// let dir = PathDir::create(example)?;
+    /// dir.create_dir("foo")?.create_file("bar.txt")?.write_str("bar")?;
+    ///
+    /// let dir_bk = dir.copy(example_bk)?;
+    /// assert_eq!(dir.tree_hash()?, dir_bk.tree_hash()?);
+    ///
+    /// dir_bk.create_file("baz.txt")?;
+    /// assert_ne!(dir.tree_hash()?, dir_bk.tree_hash()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn tree_hash(&self) -> Result<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+
+        let mut entries = BTreeMap::new();
+        for entry in self.walk() {
+            let path = match entry {
+                Ok(ref entry) => entry.as_path().to_path_buf(),
+                Err(ref err) => err.path().to_path_buf(),
+            };
+            let relative = path.strip_prefix(self).unwrap_or(Path::new(""));
+            // Symlinks are not followed, so a dangling one doesn't fail the hash.
+            let is_link = !relative.as_os_str().is_empty()
+                && PathInfo::symlink_metadata(path.as_path())
+                    .map(|meta| meta.file_type().is_symlink())
+                    .unwrap_or(false);
+            let entry = match entry {
+                _ if is_link => None,
+                Ok(entry) => Some(entry),
+                Err(err) => return Err(err),
+            };
+            entries.insert(relative.to_path_buf(), (path, entry));
+        }
+
+        let mut hasher = Sha256::new();
+        let update_bytes = |hasher: &mut Sha256, bytes: &[u8]| {
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        };
+        for (relative, (path, entry)) in entries {
+            // Join the raw components with `/` so the hash is the same on every platform, and
+            // names which aren't unicode are still distinguished.
+            let name = relative
+                .components()
+                .map(|c| c.as_os_str().as_encoded_bytes())
+                .collect::<Vec<_>>()
+                .join(&b'/');
+            update_bytes(&mut hasher, &name);
+            match entry {
+                None => {
+                    hasher.update(b"l");
+                    let target = PathInfo::read_link(path.as_path())?;
+                    update_bytes(&mut hasher, target.as_os_str().as_encoded_bytes());
+                }
+                Some(PathType::Dir(_)) => hasher.update(b"d"),
+                Some(PathType::File(file)) => {
+                    hasher.update(b"f");
+                    hasher.update(file.metadata()?.len().to_le_bytes());
+                    let mut read = file.open_read()?;
+                    io::copy(&mut read, &mut hasher)
+                        .map_err(|err| Error::new(err, "hashing", file.clone().into()))?;
+                }
+            }
+        }
+        Ok(hasher.finalize().into())
     }
 
-    /// Get the parent directory of this directory as a `PathDir`.
+    /// Find the entries under the directory whose relative path matches a glob `pattern`.
     ///
-    /// > This does not make aditional syscalls, as the parent by definition must be a directory
-    /// > and exist.
+    /// `?` matches any single character and `*` any run of characters within one path
+    /// component, so `*.log` only matches entries directly inside the directory. Matching is
+    /// only recursive where the pattern contains `**`, which matches any number of nested
+    /// directories: `**/*.log` finds every `.log` file in the tree. `[...]` character classes are
+    /// also supported, and `/` separates components on every platform.
+    ///
+    /// Matches are returned in sorted order. Wildcards also match names starting with a `.`.
+    /// Entries which can't be resolved, such as dangling symlinks, never match. Without `**`,
+    /// the tree is only walked as deep as the pattern has components.
+    ///
+    /// > This requires the `glob` feature.
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
-    /// use path_abs::PathDir;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathInfo};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let src = PathDir::new("src")?;
-    /// let proj = src.parent_dir().unwrap();
-    /// assert_eq!(PathDir::new("src/..")?, proj);
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    /// let dir = PathDir::create(example)?;
+    /// dir.create_file("a.log")?;
+    /// dir.create_dir("logs")?.create_file("b.log")?;
+    ///
+    /// let top = dir.glob("*.log")?;
+    /// assert_eq!(1, top.len());
+    /// assert!(top[0].ends_with("a.log"));
+    ///
+    /// let all = dir.glob("**/*.log")?;
+    /// assert_eq!(2, all.len());
+    /// assert!(all.iter().all(|p| p.is_file()));
+    ///
+    /// assert!(dir.glob("log?")?[0].is_dir());
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn parent_dir(&self) -> Option<PathDir> {
-        match self.parent() {
-            Ok(path) => Some(PathDir(PathAbs(Arc::new(path.to_path_buf())))),
-            Err(_) => None,
+    #[cfg(feature = "glob")]
+    pub fn glob(&self, pattern: &str) -> Result<Vec<PathType>> {
+        let invalid = |msg: String| {
+            Error::new(
+                io::Error::new(io::ErrorKind::InvalidInput, msg),
+                "globbing",
+                self.clone().into(),
+            )
+        };
+        if Path::new(pattern).has_root() || pattern.starts_with('/') {
+            return Err(invalid(format!("pattern {:?} is absolute", pattern)));
+        }
+        let compiled = glob::Pattern::new(pattern)
+            .map_err(|err| invalid(format!("invalid pattern {:?}: {}", pattern, err)))?;
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        // Only a pattern with `**` can match at any depth, otherwise each component of the
+        // pattern matches exactly one level of the tree.
+        let mut walk = self.walk();
+        if !pattern.contains("**") {
+            walk.max_depth = Some(pattern.split('/').filter(|c| !c.is_empty()).count());
+        }
+
+        // Match against the `/`-joined relative paths so the root itself (which may be a
+        // verbatim `\\?\` path on Windows) never has to be expressed as a pattern.
+        let mut matches = Vec::new();
+        for (relative, entry) in walk_lenient(self, walk)? {
+            let entry = match entry {
+                Some(entry) => entry,
+                // It can't be resolved (e.g. a dangling symlink), so it is not a match.
+                None => continue,
+            };
+            let components: Option<Vec<&str>> = relative
+                .components()
+                .map(|c| c.as_os_str().to_str())
+                .collect();
+            let relative_str = match components {
+                Some(components) => components.join("/"),
+                // A non-unicode name can never match a `str` pattern.
+                None => continue,
+            };
+            if compiled.matches_with(&relative_str, options) {
+                matches.push(entry);
+            }
         }
+        Ok(matches)
+    }
+}
+
+/// The differences between two directory trees, returned by [`PathDir::diff`].
+///
+/// All paths are relative to the root of the trees being compared.
+///
+/// [`PathDir::diff`]: struct.PathDir.html#method.diff
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirDiff {
+    /// Entries which only exist in the tree `diff` was called on.
+    pub only_in_self: Vec<PathBuf>,
+    /// Entries which only exist in the tree passed to `diff`.
+    pub only_in_other: Vec<PathBuf>,
+    /// Entries which exist in both trees but have a different type or contents.
+    pub differing: Vec<PathBuf>,
+}
+
+impl DirDiff {
+    /// Return whether the two trees were identical.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_self.is_empty() && self.only_in_other.is_empty() && self.differing.is_empty()
     }
 }
 
@@ -359,13 +1584,18 @@ pub struct ListDir {
     // TODO: this should be a reference...?
     // Or is this a good excuse to use Arc under the hood everywhere?
     dir: PathDir,
-    fsread: fs::ReadDir,
+    /// `None` if the directory could not be read, in which case `err` is yielded once.
+    fsread: Option<fs::ReadDir>,
+    err: Option<Error>,
 }
 
 impl ::std::iter::Iterator for ListDir {
     type Item = Result<PathType>;
     fn next(&mut self) -> Option<Result<PathType>> {
-        let entry = match self.fsread.next() {
+        if let Some(err) = self.err.take() {
+            return Some(Err(err));
+        }
+        let entry = match self.fsread.as_mut()?.next() {
             Some(r) => match r {
                 Ok(e) => e,
                 Err(err) => {
@@ -382,6 +1612,206 @@ impl ::std::iter::Iterator for ListDir {
     }
 }
 
+/// A recursive iterator over `PathType` objects, returned by `PathDir::walk`.
+pub struct WalkDir {
+    /// The directory to walk, taken when the walk starts.
+    root: Option<PathDir>,
+    stack: Vec<ListDir>,
+    /// How many levels of the tree to list, if limited.
+    max_depth: Option<usize>,
+    /// The error from listing the directory yielded last, returned on the next call.
+    pending: Option<Error>,
+    /// The `(device, inode)` pairs seen so far, if deduplicating.
+    #[cfg(unix)]
+    visited: Option<HashSet<(u64, u64)>>,
+    /// The identities of the directories walked so far, if following symlinks.
+    followed: Option<HashSet<DirId>>,
+}
+
+/// Identifies a directory independently of the path used to reach it.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+/// The `(device, inode)` pair of the directory.
+#[cfg(unix)]
+fn dir_id(dir: &PathDir) -> Result<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = dir.metadata()?;
+    Ok((meta.dev(), meta.ino()))
+}
+
+/// The canonicalized path of the directory, as file ids are not available on stable Rust.
+#[cfg(not(unix))]
+fn dir_id(dir: &PathDir) -> Result<DirId> {
+    Ok(dir.canonicalize()?.as_path().to_path_buf())
+}
+
+impl WalkDir {
+    /// Skip entries whose inode has already been visited during the walk.
+    ///
+    /// Hardlinked files (and, on systems that allow it, hardlinked directories) are then only
+    /// yielded once, and a directory is never walked twice. This costs a `stat` per entry, so it
+    /// is off by default.
+    ///
+    /// When [`follow_symlinks`] is also enabled, directories are not skipped: one which was already
+    /// walked is yielded but not descended into again.
+    ///
+    /// [`follow_symlinks`]: struct.WalkDir.html#method.follow_symlinks
+    ///
+    /// This is only available on Unix.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::fs;
+    /// use path_abs::{PathDir, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let dir = PathDir::create(example)?;
+    /// let foo = dir.create_file("foo.txt")?;
+    /// fs::hard_link(&foo, dir.concat("bar.txt")?)?;
+    ///
+    /// assert_eq!(2, dir.walk().count());
+    /// assert_eq!(1, dir.walk().dedup_inodes(true).count());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(unix)]
+    pub fn dedup_inodes(mut self, dedup: bool) -> WalkDir {
+        self.visited = if dedup { Some(HashSet::new()) } else { None };
+        self
+    }
+
+    /// Descend into symlinks to directories instead of only yielding them.
+    ///
+    /// Symlinks can form cycles (e.g. a link to one of its own parents), so the identity of each
+    /// directory walked is recorded (the device and inode on Unix, the canonicalized path
+    /// elsewhere) and a directory which was already walked is yielded but not descended into
+    /// again. This also means a directory reachable through several links is only walked once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathOps};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example");
+    /// # let other = &tmp.path().join("other");
+    /// let dir = PathDir::create(example)?;
+    /// let foo = dir.create_dir("foo")?;
+    /// foo.create_file("bar.txt")?;
+    /// // A link back to the top of the tree, and one to a tree outside of it.
+    /// dir.symlink(foo.concat("loop")?)?;
+    /// let other = PathDir::create(other)?;
+    /// other.create_file("baz.txt")?;
+    /// other.symlink(foo.concat("other")?)?;
+    ///
+    /// // foo, foo/bar.txt, foo/loop and foo/other
+    /// assert_eq!(4, dir.walk().count());
+    /// // Also foo/other/baz.txt. foo/loop is the directory being walked, so it is pruned.
+    /// assert_eq!(5, dir.walk().follow_symlinks(true).count());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn follow_symlinks(mut self, follow: bool) -> WalkDir {
+        self.followed = if follow { Some(HashSet::new()) } else { None };
+        self
+    }
+
+    /// Whether to descend into `dir`, recording it as walked if so.
+    fn should_descend(&mut self, dir: &PathDir) -> Result<bool> {
+        match self.followed {
+            Some(ref mut followed) => Ok(followed.insert(dir_id(dir)?)),
+            None => Ok(!dir.symlink_metadata()?.file_type().is_symlink()),
+        }
+    }
+
+    /// Record the inode of `path`, returning whether it was already visited.
+    #[cfg(unix)]
+    fn seen<P: PathInfo>(&mut self, path: &P) -> Result<bool> {
+        use std::os::unix::fs::MetadataExt;
+
+        match self.visited {
+            Some(ref mut visited) => {
+                let meta = path.metadata()?;
+                Ok(!visited.insert((meta.dev(), meta.ino())))
+            }
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn seen<P: PathInfo>(&mut self, _path: &P) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+impl ::std::iter::Iterator for WalkDir {
+    type Item = Result<PathType>;
+    fn next(&mut self) -> Option<Result<PathType>> {
+        if let Some(root) = self.root.take() {
+            if let Err(err) = self.seen(&root) {
+                return Some(Err(err));
+            }
+            if let Err(err) = self.should_descend(&root) {
+                return Some(Err(err));
+            }
+            match root.list() {
+                Ok(list) => self.stack.push(list),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        if let Some(err) = self.pending.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let entry = match self.stack.last_mut()?.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            // Directories reached through symlinks are deduplicated by `should_descend`
+            // instead, so they are still yielded even if they were already walked.
+            let seen = match entry {
+                PathType::Dir(_) if self.followed.is_some() => Ok(false),
+                _ => self.seen(&entry),
+            };
+            match seen {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(err) => return Some(Err(err)),
+            }
+
+            let below_max = self.max_depth.map_or(true, |max| self.stack.len() < max);
+            if let (PathType::Dir(ref dir), true) = (&entry, below_max) {
+                let descend = match self.should_descend(dir) {
+                    Ok(descend) => descend,
+                    Err(err) => return Some(Err(err)),
+                };
+                if descend {
+                    // Yield the directory itself before the error from listing it.
+                    match dir.list() {
+                        Ok(list) => self.stack.push(list),
+                        Err(err) => self.pending = Some(err),
+                    }
+                }
+            }
+            return Some(Ok(entry));
+        }
+    }
+}
+
 impl fmt::Debug for PathDir {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -468,12 +1898,60 @@ impl From<PathDir> for PathBuf {
     }
 }
 
+/// Iterate over the contents of the directory, the same as [`PathDir::list`].
+///
+/// If the directory cannot be read, the iterator yields that error once and then stops.
+///
+/// [`PathDir::list`]: struct.PathDir.html#method.list
+///
+/// # Examples
+/// ```rust
+/// # extern crate path_abs;
+/// use path_abs::{PathDir, PathInfo};
+///
+/// # fn try_main() -> ::std::io::Result<()> {
+/// let src = PathDir::new("src")?;
+/// for entry in &src {
+///     let entry = entry?;
+///     assert!(entry.exists());
+/// }
+/// # Ok(()) } fn main() { try_main().unwrap() }
+/// ```
+impl IntoIterator for &PathDir {
+    type Item = Result<PathType>;
+    type IntoIter = ListDir;
+
+    fn into_iter(self) -> ListDir {
+        match self.list() {
+            Ok(list) => list,
+            Err(err) => ListDir {
+                dir: self.clone(),
+                fsread: None,
+                err: Some(err),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::{PathAbs, PathDir, PathFile, PathOps, PathType};
+    use super::super::{PathAbs, PathDir, PathFile, PathInfo, PathOps, PathType};
     use std::collections::HashSet;
+    use std::io;
     use tempfile::TempDir;
 
+    #[test]
+    fn find_up_marker() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        let marker = PathFile::create(tmp_abs.concat("marker.toml").unwrap()).unwrap();
+        let nested = PathDir::create_all(tmp_abs.concat("a/b").unwrap()).unwrap();
+
+        assert_eq!(nested.find_up("marker.toml"), Some(PathType::File(marker)));
+        assert_eq!(nested.find_up("does-not-exist-anywhere.toml"), None);
+    }
+
     #[test]
     fn sanity_list() {
         let tmp_dir = TempDir::new().expect("create temp dir");
@@ -500,6 +1978,250 @@ mod tests {
         let _: PathAbs = foo_dir.into();
         let _: PathAbs = bar_file.into();
     }
+
+    #[test]
+    fn ensure_wrong_type() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        let foo_path = tmp_abs.concat("foo").unwrap();
+        let foo_file = PathFile::ensure(&foo_path).unwrap();
+        assert_eq!(foo_file, PathFile::ensure(&foo_path).unwrap());
+        let err = PathDir::ensure(&foo_path).expect_err("file ensured as dir");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+
+        let bar_path = tmp_abs.concat("bar").unwrap();
+        let bar_dir = PathDir::ensure(&bar_path).unwrap();
+        assert_eq!(bar_dir, PathDir::ensure(&bar_path).unwrap());
+        let err = PathFile::ensure(&bar_path).expect_err("dir ensured as file");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn copy_symlink_loop() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        let src = PathDir::create(tmp_abs.concat("src").unwrap()).unwrap();
+        PathFile::create(src.concat("foo.txt").unwrap()).unwrap();
+        src.symlink(src.concat("loop").unwrap()).unwrap();
+
+        let err = src
+            .copy(tmp_abs.concat("followed").unwrap())
+            .expect_err("followed a symlink loop");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+
+        let dst = src
+            .copy_preserve_symlinks(tmp_abs.concat("preserved").unwrap())
+            .unwrap();
+        let ty = dst.concat("loop").unwrap().symlink_metadata().unwrap();
+        assert!(ty.file_type().is_symlink());
+        assert!(dst.concat("foo.txt").unwrap().is_file());
+    }
+
+    #[test]
+    fn copy_into_itself() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        let err = tmp_abs
+            .copy_preserve_symlinks(tmp_abs.concat("inner").unwrap())
+            .expect_err("copied a directory into itself");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+        assert!(!tmp_abs.join("inner").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_unreadable_subdir() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+        let locked = tmp_abs.create_dir("locked").unwrap();
+        locked.create_file("hidden.txt").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permissions don't apply to root, so there is nothing to test.
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let mut walk = tmp_abs.walk();
+        assert_eq!(walk.next().unwrap().unwrap(), PathType::Dir(locked.clone()));
+        let err = walk
+            .next()
+            .unwrap()
+            .expect_err("listed an unreadable directory");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::PermissionDenied);
+        assert!(walk.next().is_none());
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_dedup_and_follow_symlinks() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+        let foo = tmp_abs.create_dir("foo").unwrap();
+        let bar = foo.create_file("bar.txt").unwrap();
+        ::std::fs::hard_link(&bar, foo.concat("baz.txt").unwrap()).unwrap();
+        let again = foo.symlink(tmp_abs.concat("again").unwrap()).unwrap();
+
+        let walked: Vec<PathType> = tmp_abs
+            .walk()
+            .dedup_inodes(true)
+            .follow_symlinks(true)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // foo and again are both yielded, but only one of them is walked and only one of the
+        // hardlinked files is yielded.
+        assert_eq!(walked.len(), 3, "{:?}", walked);
+        assert!(walked.iter().any(|p| p.as_path() == foo.as_path()));
+        assert!(walked.iter().any(|p| p.as_path() == again.as_path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn diff_broken_symlink() {
+        use std::os::unix::fs::symlink;
+        use std::path::PathBuf;
+
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+        let ours = tmp_abs.create_dir("ours").unwrap();
+        let theirs = tmp_abs.create_dir("theirs").unwrap();
+        for dir in &[&ours, &theirs] {
+            dir.create_file("same.txt")
+                .unwrap()
+                .write_str("same")
+                .unwrap();
+            symlink("missing", dir.concat("both").unwrap()).unwrap();
+        }
+        symlink("missing", ours.concat("only_ours").unwrap()).unwrap();
+
+        let diff = ours.diff(&theirs).unwrap();
+        assert_eq!(diff.only_in_self, [PathBuf::from("only_ours")]);
+        assert!(diff.only_in_other.is_empty());
+        assert_eq!(diff.differing, [PathBuf::from("both")]);
+    }
+
+    #[cfg(all(unix, feature = "digest"))]
+    #[test]
+    fn tree_hash_raw_names_and_links() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::symlink;
+
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        // Names which only differ in their invalid bytes.
+        let a = tmp_abs.create_dir("a").unwrap();
+        a.create_file(OsStr::from_bytes(b"\xFF")).unwrap();
+        let b = tmp_abs.create_dir("b").unwrap();
+        b.create_file(OsStr::from_bytes(b"\xFE")).unwrap();
+        assert_ne!(a.tree_hash().unwrap(), b.tree_hash().unwrap());
+
+        // Dangling symlinks are hashed by their target.
+        let c = tmp_abs.create_dir("c").unwrap();
+        symlink("missing", c.concat("link").unwrap()).unwrap();
+        let d = tmp_abs.create_dir("d").unwrap();
+        symlink("missing", d.concat("link").unwrap()).unwrap();
+        assert_eq!(c.tree_hash().unwrap(), d.tree_hash().unwrap());
+        let e = tmp_abs.create_dir("e").unwrap();
+        symlink("other", e.concat("link").unwrap()).unwrap();
+        assert_ne!(c.tree_hash().unwrap(), e.tree_hash().unwrap());
+    }
+
+    #[test]
+    fn into_iter_unreadable() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+
+        let gone = PathDir::create(tmp_abs.concat("gone").unwrap()).unwrap();
+        gone.clone().remove().unwrap();
+
+        let mut iter = (&gone).into_iter();
+        let err = iter.next().unwrap().expect_err("read a removed directory");
+        assert_eq!(err.io_error().kind(), io::ErrorKind::NotFound);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn create_all_names_failing_component() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+        let file = PathFile::create(tmp_abs.concat("file.txt").unwrap()).unwrap();
+
+        let err = PathDir::create_all(file.concat("sub/dir").unwrap()).unwrap_err();
+        assert_eq!("creating-all", err.action());
+        let msg = err.io_error().to_string();
+        assert!(
+            msg.contains(&format!("{} is not a directory", file.as_path().display())),
+            "{}",
+            msg
+        );
+        assert!(
+            msg.contains(&format!(
+                "deepest existing ancestor is {}",
+                tmp_abs.as_path().display()
+            )),
+            "{}",
+            msg
+        );
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn glob_relative_to_root() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        // On Windows this is a verbatim `\\?\C:\...` path, which must not end up in the pattern.
+        let tmp_abs = PathDir::new(tmp_dir.path()).unwrap();
+        tmp_abs.create_file("a.log").unwrap();
+        tmp_abs.create_file("[x].log").unwrap();
+        let logs = tmp_abs.create_dir("logs").unwrap();
+        logs.create_file("b.log").unwrap();
+        logs.create_file("c.txt").unwrap();
+
+        let relative = |pattern: &str| -> Vec<String> {
+            tmp_abs
+                .glob(pattern)
+                .unwrap()
+                .iter()
+                .map(|p| {
+                    let rel = p.as_path().strip_prefix(&tmp_abs).unwrap();
+                    let parts: Vec<_> = rel.iter().map(|c| c.to_str().unwrap()).collect();
+                    parts.join("/")
+                })
+                .collect()
+        };
+
+        assert_eq!(relative("*.log"), ["[x].log", "a.log"]);
+        assert_eq!(relative("**/*.log"), ["[x].log", "a.log", "logs/b.log"]);
+        assert_eq!(relative("logs/*"), ["logs/b.log", "logs/c.txt"]);
+        assert_eq!(relative("[[]x].log"), ["[x].log"]);
+        assert!(relative("missing/*").is_empty());
+
+        let err = tmp_abs.glob("[").unwrap_err();
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+
+        // Without `**` only the levels named by the pattern are listed.
+        let mut walk = tmp_abs.walk();
+        walk.max_depth = Some(1);
+        assert_eq!(3, walk.count());
+
+        #[cfg(unix)]
+        {
+            ::std::os::unix::fs::symlink("missing", tmp_abs.concat("dangling.log").unwrap())
+                .unwrap();
+            assert_eq!(relative("*.log"), ["[x].log", "a.log"]);
+            assert_eq!(relative("**/*.log"), ["[x].log", "a.log", "logs/b.log"]);
+        }
+    }
 }
 
 impl PathOps for PathDir {
@@ -514,6 +2236,22 @@ impl PathOps for PathDir {
         Self::Output::new_unchecked(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.0.concat_all(parts)
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.0.join_all(parts)
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         self.0.with_file_name(file_name)
     }
@@ -521,6 +2259,186 @@ impl PathOps for PathDir {
     fn with_extension<S: AsRef<ffi::OsStr>>(&self, extension: S) -> Self::Output {
         self.0.with_extension(extension)
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        self.0.with_file_stem(stem)
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        self.0.normalized()
+    }
+}
+
+/// Collect the entries of `walk` by their path relative to `root`.
+///
+/// Entries which fail to resolve (e.g. dangling symlinks) or directories which fail to be listed
+/// are kept as `None` instead of failing, so the rest of the tree can still be inspected.
+fn walk_lenient(root: &PathDir, walk: WalkDir) -> Result<BTreeMap<PathBuf, Option<PathType>>> {
+    let mut entries = BTreeMap::new();
+    for entry in walk {
+        let (relative, entry) = match entry {
+            Ok(entry) => {
+                let relative = entry
+                    .as_path()
+                    .strip_prefix(root)
+                    .expect("walked entries are inside the root")
+                    .to_path_buf();
+                (relative, Some(entry))
+            }
+            // An error about an entry, rather than the root itself, only affects that entry.
+            Err(err) => match err.path().strip_prefix(root) {
+                Ok(relative) if !relative.as_os_str().is_empty() => (relative.to_path_buf(), None),
+                _ => return Err(err),
+            },
+        };
+        entries.insert(relative, entry);
+    }
+    Ok(entries)
+}
+
+/// Recursively copy `src` into `dst`, used by `PathDir::copy*`.
+fn copy_tree(src: &PathDir, dst: &Path, preserve_symlinks: bool) -> Result<()> {
+    // Check for containment before creating `dst`, so a failed copy doesn't leave it behind.
+    let src_can = src.canonicalize()?;
+    let dst_can: PathBuf = match (dst.exists(), dst.parent(), dst.file_name()) {
+        (false, Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            PathInfo::canonicalize(parent)?.as_path().join(name)
+        }
+        _ => PathInfo::canonicalize(dst)?.into(),
+    };
+    if dst_can.starts_with(&src_can) {
+        return Err(Error::new(
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot copy a directory into itself",
+            ),
+            &format!("copying {} from", dst.display()),
+            src.clone().into(),
+        ));
+    }
+    create_dir_exists_ok(dst)?;
+
+    let mut ancestors = vec![src_can.into()];
+    copy_tree_inner(src.as_path(), dst, preserve_symlinks, &mut ancestors)
+}
+
+fn copy_tree_inner(
+    src: &Path,
+    dst: &Path,
+    preserve_symlinks: bool,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let fsread = fs::read_dir(src)
+        .map_err(|err| Error::new(err, "reading dir", src.to_path_buf().into()))?;
+    for entry in fsread {
+        let entry =
+            entry.map_err(|err| Error::new(err, "iterating over", src.to_path_buf().into()))?;
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        if preserve_symlinks
+            && PathInfo::symlink_metadata(from.as_path())?
+                .file_type()
+                .is_symlink()
+        {
+            let target = PathInfo::read_link(from.as_path())?;
+            let linked = if from.is_dir() {
+                symlink_dir(&target, &to)
+            } else {
+                symlink_file(&target, &to)
+            };
+            linked.map_err(|err| {
+                Error::new(
+                    err,
+                    &format!("copying link {} from", to.display()),
+                    from.into(),
+                )
+            })?;
+        } else if from.is_dir() {
+            let from_can: PathBuf = PathInfo::canonicalize(from.as_path())?.into();
+            if ancestors.contains(&from_can) {
+                return Err(Error::new(
+                    io::Error::new(io::ErrorKind::InvalidInput, "symlink loop detected"),
+                    &format!("copying {} from", to.display()),
+                    from.into(),
+                ));
+            }
+            create_dir_exists_ok(&to)?;
+            ancestors.push(from_can);
+            copy_tree_inner(&from, &to, preserve_symlinks, ancestors)?;
+            ancestors.pop();
+        } else {
+            fs::copy(&from, &to).map_err(|err| {
+                Error::new(err, &format!("copying {} from", to.display()), from.into())
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Annotate an error from `fs::create_dir_all(path)` with which component caused it.
+///
+/// The ancestors of `path` are checked from the root down to find the first one which is
+/// missing or not a directory. Returns `err` unchanged if there is none (i.e. the tree was changed
+/// concurrently).
+fn locate_create_all_failure(path: &Path, err: io::Error) -> io::Error {
+    let mut ancestors: Vec<&Path> = path
+        .ancestors()
+        .filter(|a| !a.as_os_str().is_empty())
+        .collect();
+    ancestors.reverse();
+
+    let mut deepest: Option<&Path> = None;
+    for ancestor in ancestors {
+        let problem = match fs::metadata(ancestor) {
+            Ok(ref m) if m.is_dir() => {
+                deepest = Some(ancestor);
+                continue;
+            }
+            Ok(_) => "is not a directory",
+            Err(_) => "could not be created",
+        };
+        let deepest = match deepest {
+            Some(d) => format!("deepest existing ancestor is {}", d.display()),
+            None => "no ancestor exists".to_string(),
+        };
+        let msg = format!("{}: {} {} ({})", err, ancestor.display(), problem, deepest);
+        return io::Error::new(err.kind(), msg);
+    }
+    err
+}
+
+/// A random number, good enough to make names unlikely to collide.
+///
+/// Each `RandomState` is seeded with fresh random keys, which is mixed with the time and a
+/// counter so that even repeated calls in one thread differ.
+fn random_u64() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u32(::std::process::id());
+    if let Ok(since) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(since.as_nanos());
+    }
+    hasher.finish()
+}
+
+/// Create a single directory, succeeding if it already exists.
+fn create_dir_exists_ok(path: &Path) -> Result<()> {
+    if let Err(err) = fs::create_dir(path) {
+        if err.kind() != io::ErrorKind::AlreadyExists {
+            return Err(Error::new(err, "creating", path.to_path_buf().into()));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(target_os = "wasi")]
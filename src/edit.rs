@@ -22,7 +22,7 @@ use super::{Error, PathAbs, PathInfo, Result};
 /// ```rust
 /// # extern crate path_abs;
 /// # extern crate tempfile;
-/// use std::io::{Read, Seek, Write, SeekFrom};
+/// use std::io::{Read, Write};
 /// use path_abs::FileEdit;
 ///
 /// # fn try_main() -> ::std::io::Result<()> {
@@ -35,7 +35,7 @@ use super::{Error, PathAbs, PathInfo, Result};
 ///
 /// let mut s = String::new();
 /// edit.write_all(expected.as_bytes())?;
-/// edit.seek(SeekFrom::Start(0))?;
+/// edit.rewind()?;
 /// edit.read_to_string(&mut s)?;
 ///
 /// assert_eq!(expected, s);
@@ -70,6 +70,35 @@ impl FileEdit {
         FileEdit::open(path, options)
     }
 
+    /// Create a new file in editing mode, failing if it already exists.
+    ///
+    /// The check and the creation are a single atomic operation, which makes this useful for
+    /// exclusively initializing a file that is then read back.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io;
+    /// use path_abs::FileEdit;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let mut edit = FileEdit::create_new(example)?;
+    /// edit.write_str("foo")?;
+    ///
+    /// let err = FileEdit::create_new(example).unwrap_err();
+    /// assert_eq!(io::ErrorKind::AlreadyExists, err.io_error().kind());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn create_new<P: AsRef<Path>>(path: P) -> Result<FileEdit> {
+        let mut options = fs::OpenOptions::new();
+        options.create_new(true);
+        FileEdit::open(path, options)
+    }
+
     /// Open the file for appending, creating it if it doesn't exist.
     pub fn append<P: AsRef<Path>>(path: P) -> Result<FileEdit> {
         let mut options = fs::OpenOptions::new();
@@ -156,6 +185,19 @@ impl FileEdit {
         Ok(s)
     }
 
+    /// Read what remains of the file, appending it to `buf` and returning the number of bytes
+    /// read.
+    ///
+    /// This is [`Read::read_to_end`] with the new error type.
+    ///
+    /// [`Read::read_to_end`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end
+    pub fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        self.0
+            .file
+            .read_to_end(buf)
+            .map_err(|err| Error::new(err, "reading", self.0.path.clone().into()))
+    }
+
     /// Shortcut to `self.write_all(s.as_bytes())` with slightly
     /// improved error message.
     pub fn write_str(&mut self, s: &str) -> Result<()> {
@@ -172,6 +214,92 @@ impl FileEdit {
             .flush()
             .map_err(|err| Error::new(err, "flushing", self.0.path.clone().into()))
     }
+
+    /// Seek to the start of the file, the same as [`Seek::rewind`] but with the new error type.
+    ///
+    /// [`Seek::rewind`]: https://doc.rust-lang.org/std/io/trait.Seek.html#method.rewind
+    pub fn rewind(&mut self) -> Result<()> {
+        self.0
+            .file
+            .seek(io::SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|err| Error::new(err, "seeking", self.0.path.clone().into()))
+    }
+
+    /// Write `buf` starting at byte `offset`, returning the number of bytes written.
+    ///
+    /// This takes `&self` and doesn't need to seek first, which suits writing fixed-size records
+    /// into a preallocated file. Like [`Write::write`] it may write fewer bytes than `buf` holds.
+    ///
+    /// On Unix this uses `pwrite`, which does not change the position of the file. On Windows
+    /// the position of the file is moved to just after the bytes written. On other platforms it
+    /// returns `io::ErrorKind::Unsupported`.
+    ///
+    /// > Don't use this on a handle opened in append mode: on Linux the data is appended to the
+    /// > end of the file regardless of `offset`, and other platforms differ.
+    ///
+    /// [`Write::write`]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.write
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("aaaabbbbcccc")?;
+    ///
+    /// let edit = file.open_edit()?;
+    /// assert_eq!(4, edit.write_at(b"BBBB", 4)?);
+    /// assert_eq!("aaaaBBBBcccc", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize> {
+        self.0.write_at(buf, offset)
+    }
+
+    /// Memory map the file for reading and writing.
+    ///
+    /// The returned [`MmapMut`] dereferences to `&mut [u8]`, so the file can be edited in place.
+    /// Its length is fixed to the size of the file when it was mapped: use [`set_len`] first to
+    /// grow the file. Call `MmapMut::flush` to make sure changes are written to disk.
+    ///
+    /// > **Caveat:** as with [`PathFile::mmap`], the file must not be modified or truncated by
+    /// > anything else while it is mapped.
+    ///
+    /// > This requires the `mmap` feature.
+    ///
+    /// [`MmapMut`]: https://docs.rs/memmap2/0.9/memmap2/struct.MmapMut.html
+    /// [`set_len`]: #method.set_len
+    /// [`PathFile::mmap`]: struct.PathFile.html#method.mmap
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.txt");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo bar")?;
+    ///
+    /// let mut map = file.open_edit()?.mmap_mut()?;
+    /// map[..3].copy_from_slice(b"FOO");
+    /// map.flush()?;
+    /// assert_eq!("FOO bar", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub fn mmap_mut(&self) -> Result<::memmap2::MmapMut> {
+        // Safety: see the caveat above, the file must not be modified while mapped.
+        unsafe { ::memmap2::MmapMut::map_mut(&self.0.file) }
+            .map_err(|err| Error::new(err, "memory mapping", self.0.path.clone().into()))
+    }
 }
 
 impl fmt::Debug for FileEdit {
@@ -9,8 +9,13 @@ use std::ffi;
 use std::fmt;
 use std::fs;
 use std::io;
+use std::io::BufRead;
+use std::time::SystemTime;
 use std_prelude::*;
 
+use super::open::FileOpen;
+#[cfg(feature = "async-tokio")]
+use super::{AsyncFileRead, AsyncFileWrite};
 use super::{Error, Result};
 use super::{FileEdit, FileRead, FileWrite, PathAbs, PathDir, PathInfo, PathOps};
 
@@ -48,6 +53,33 @@ impl PathFile {
         PathFile(PathAbs::new_unchecked(path))
     }
 
+    /// Instantiate a new `PathFile` if the path exists, returning `None` if it doesn't.
+    ///
+    /// Unlike [`new`], a missing path is not an error. An error is still returned if the path
+    /// exists but is not a file, or if it could not be queried.
+    ///
+    /// [`new`]: #method.new
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// assert!(PathFile::try_new("src/lib.rs")?.is_some());
+    /// assert!(PathFile::try_new("src/does-not-exist.rs")?.is_none());
+    /// assert!(PathFile::try_new("src").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn try_new<P: AsRef<Path>>(path: P) -> Result<Option<PathFile>> {
+        let abs = PathAbs::new(path)?;
+        match abs.metadata() {
+            Ok(_) => PathFile::try_from(abs).map(Some),
+            Err(ref err) if err.io_error().kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Convert a `PathAbs` into a `PathFile`, first validating that the path is a file.
     ///
     /// # Error
@@ -96,222 +128,1351 @@ impl PathFile {
     /// assert_eq!(PathDir::new("src")?, src);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn parent_dir(&self) -> PathDir {
-        let path = self.parent().expect("PathFile did not have a parent.");
-        PathDir::new_unchecked(PathAbs::new_unchecked(path.to_path_buf()))
+    pub fn parent_dir(&self) -> PathDir {
+        let path = self.parent().expect("PathFile did not have a parent.");
+        PathDir::new_unchecked(PathAbs::new_unchecked(path.to_path_buf()))
+    }
+
+    /// Instantiate a new `PathFile`, creating an empty file if it doesn't exist.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    ///
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    ///
+    /// let file = PathFile::create(example)?;
+    ///
+    /// // It can be done twice with no effect.
+    /// let _ = PathFile::create(example)?;
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<PathFile> {
+        fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&path)
+            .map_err(|err| Error::new(err, "opening", path.as_ref().to_path_buf().into()))?;
+        PathFile::new(path)
+    }
+
+    /// Instantiate a new `PathFile`, creating any missing parent directories and then an empty
+    /// file if it doesn't exist.
+    ///
+    /// This is the file equivalent of [`PathDir::create_all`]. Errors while creating the parent
+    /// directories have the action `"creating-all"`.
+    ///
+    /// [`PathDir::create_all`]: struct.PathDir.html#method.create_all
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathFile, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example/long/path.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create_all(example)?;
+    /// assert!(file.is_file());
+    /// assert!(file.parent_dir().is_dir());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn create_all<P: AsRef<Path>>(path: P) -> Result<PathFile> {
+        let abs = PathAbs::new(path)?;
+        if let Some(parent) = abs.as_path().parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| Error::new(err, "creating-all", parent.to_path_buf().into()))?;
+        }
+        PathFile::create(abs)
+    }
+
+    /// Instantiate a new `PathFile`, creating an empty file if it doesn't exist.
+    ///
+    /// Unlike [`create`], this first checks whether the path exists and returns
+    /// `io::ErrorKind::InvalidInput` if it is not a file.
+    ///
+    /// [`create`]: struct.PathFile.html#method.create
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// let example_dir = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// # let example_dir = &tmp.path().join(example_dir);
+    ///
+    /// let file = PathFile::ensure(example)?;
+    /// assert_eq!(file, PathFile::ensure(example)?);
+    ///
+    /// PathDir::create(example_dir)?;
+    /// assert!(PathFile::ensure(example_dir).is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn ensure<P: AsRef<Path>>(path: P) -> Result<PathFile> {
+        let abs = PathAbs::new(path)?;
+        if abs.exists() {
+            PathFile::try_from(abs)
+        } else {
+            PathFile::create(abs)
+        }
+    }
+
+    /// Read the entire contents of the file into a `String`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar";
+    /// file.write_str(expected)?;
+    /// assert_eq!(expected, file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_string(&self) -> Result<String> {
+        let mut f = self.open_read()?;
+        f.read_string()
+    }
+
+    /// Read the entire contents of the file into a `String`, replacing invalid UTF-8 with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of failing.
+    ///
+    /// > The replacement alters the data: the original bytes can't be recovered from the result.
+    /// > Use [`read_string`] when the contents must be exact.
+    ///
+    /// [`read_string`]: #method.read_string
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.log");
+    /// let file = PathFile::create(example)?;
+    /// std::fs::write(&file, b"ok\nbad \xFF byte\n")?;
+    ///
+    /// assert!(file.read_string().is_err());
+    /// assert_eq!("ok\nbad \u{FFFD} byte\n", file.read_string_lossy()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_string_lossy(&self) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.open_read()?.read_to_end(&mut bytes)?;
+        Ok(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        })
+    }
+
+    /// Read the entire contents of a UTF-16 encoded file into a `String`.
+    ///
+    /// A leading byte order mark selects little or big endian and is removed. Without one the
+    /// file is assumed to be little endian, as written by most Windows tools. Odd lengths and
+    /// unpaired surrogates are `io::ErrorKind::InvalidData` errors.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.txt");
+    /// let file = PathFile::create(example)?;
+    /// file.write_utf16_le("héllo")?;
+    /// assert_eq!("héllo", file.read_utf16()?);
+    ///
+    /// // Big endian, with a byte order mark.
+    /// std::fs::write(&file, b"\xFE\xFF\x00h\x00i")?;
+    /// assert_eq!("hi", file.read_utf16()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_utf16(&self) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.open_read()?.read_to_end(&mut bytes)?;
+        let invalid = |msg: String| {
+            Error::new(
+                io::Error::new(io::ErrorKind::InvalidData, msg),
+                "reading",
+                self.clone().into(),
+            )
+        };
+
+        let (bytes, big_endian) = match bytes.get(..2) {
+            Some([0xFF, 0xFE]) => (&bytes[2..], false),
+            Some([0xFE, 0xFF]) => (&bytes[2..], true),
+            _ => (&bytes[..], false),
+        };
+        if bytes.len() % 2 != 0 {
+            return Err(invalid(
+                "UTF-16 data has an odd number of bytes".to_string(),
+            ));
+        }
+        let units = bytes.chunks(2).map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if big_endian {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        });
+        ::std::char::decode_utf16(units)
+            .collect::<::std::result::Result<String, _>>()
+            .map_err(|err| invalid(format!("invalid UTF-16: {}", err)))
+    }
+
+    /// Read the entire contents of the file and split it into lines.
+    ///
+    /// Lines are split on `\n` or `\r\n` and the line endings are not included, the same as
+    /// [`str::lines`].
+    ///
+    /// [`str::lines`]: https://doc.rust-lang.org/stable/std/primitive.str.html#method.lines
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo\nbar\r\n\nbaz\n")?;
+    ///
+    /// assert_eq!(vec!["foo", "bar", "", "baz"], file.read_lines()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_lines(&self) -> Result<Vec<String>> {
+        Ok(self.read_string()?.lines().map(String::from).collect())
+    }
+
+    /// Count the lines in the file without reading it all into memory.
+    ///
+    /// The file is streamed through a buffered reader counting `\n` bytes. A final line without a
+    /// trailing newline is counted too, so the result matches `read_lines()?.len()`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// assert_eq!(0, file.lines_count()?);
+    ///
+    /// file.write_str("foo\nbar\n")?;
+    /// assert_eq!(2, file.lines_count()?);
+    ///
+    /// file.write_str("foo\nbar\nbaz")?;
+    /// assert_eq!(3, file.lines_count()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn lines_count(&self) -> Result<usize> {
+        let file =
+            fs::File::open(self).map_err(|err| Error::new(err, "opening", self.clone().into()))?;
+        let mut reader = io::BufReader::new(file);
+        let mut count = 0;
+        let mut last = b'\n';
+        loop {
+            let len = {
+                let buf = reader
+                    .fill_buf()
+                    .map_err(|err| Error::new(err, "reading", self.clone().into()))?;
+                if buf.is_empty() {
+                    break;
+                }
+                count += buf.iter().filter(|&&b| b == b'\n').count();
+                last = buf[buf.len() - 1];
+                buf.len()
+            };
+            reader.consume(len);
+        }
+        if last != b'\n' {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Return whether this file has exactly the same contents as `other`.
+    ///
+    /// The lengths are compared first, then both files are streamed in lockstep and the
+    /// comparison stops at the first difference. This is cheaper than hashing both files when
+    /// they are likely to differ.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let foo = &tmp.path().join("foo.txt");
+    /// # let bar = &tmp.path().join("bar.txt");
+    /// let foo = PathFile::create(foo)?;
+    /// let bar = PathFile::create(bar)?;
+    /// foo.write_str("foo\nbar")?;
+    /// bar.write_str("foo\nbar")?;
+    /// assert!(foo.same_contents(&bar)?);
+    ///
+    /// bar.write_str("foo\nbaz")?;
+    /// assert!(!foo.same_contents(&bar)?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn same_contents(&self, other: &PathFile) -> Result<bool> {
+        if self.metadata()?.len() != other.metadata()?.len() {
+            return Ok(false);
+        }
+
+        let open = |path: &PathFile| {
+            fs::File::open(path)
+                .map(io::BufReader::new)
+                .map_err(|err| Error::new(err, "opening", path.clone().into()))
+        };
+        let (mut ours, mut theirs) = (open(self)?, open(other)?);
+        loop {
+            let len = {
+                let a = ours
+                    .fill_buf()
+                    .map_err(|err| Error::new(err, "reading", self.clone().into()))?;
+                let b = theirs
+                    .fill_buf()
+                    .map_err(|err| Error::new(err, "reading", other.clone().into()))?;
+                if a.is_empty() || b.is_empty() {
+                    // The files may have changed since their lengths were compared.
+                    return Ok(a.is_empty() && b.is_empty());
+                }
+                let len = a.len().min(b.len());
+                if a[..len] != b[..len] {
+                    return Ok(false);
+                }
+                len
+            };
+            ours.consume(len);
+            theirs.consume(len);
+        }
+    }
+
+    /// Read the last `n` lines of the file.
+    ///
+    /// The file is read backwards from the end in chunks until enough lines have been found, so
+    /// this is cheap even for very large files. Lines are split the same as [`read_lines`] and a
+    /// final line without a trailing newline is included.
+    ///
+    /// [`read_lines`]: #method.read_lines
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.log";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("one\ntwo\nthree\n")?;
+    ///
+    /// assert_eq!(vec!["two", "three"], file.read_tail(2)?);
+    /// assert_eq!(vec!["one", "two", "three"], file.read_tail(10)?);
+    ///
+    /// file.append_str("four")?;
+    /// assert_eq!(vec!["three", "four"], file.read_tail(2)?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_tail(&self, n: usize) -> Result<Vec<String>> {
+        const CHUNK: u64 = 8 * 1024;
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+
+        let map_err = |err| Error::new(err, "reading", self.clone().into());
+        let mut file =
+            fs::File::open(self).map_err(|err| Error::new(err, "opening", self.clone().into()))?;
+        let len = file.metadata().map_err(map_err)?.len();
+
+        // Read chunks backwards until the tail contains more than `n` line breaks (ignoring the
+        // one ending the file), or the whole file has been read.
+        let mut chunks = Vec::new();
+        let mut breaks = 0;
+        let mut pos = len;
+        while pos > 0 && breaks <= n {
+            let start = pos.saturating_sub(CHUNK);
+            let mut chunk = vec![0; (pos - start) as usize];
+            file.seek(io::SeekFrom::Start(start)).map_err(map_err)?;
+            file.read_exact(&mut chunk).map_err(map_err)?;
+            breaks += chunk.iter().filter(|&&b| b == b'\n').count();
+            if pos == len && chunk.ends_with(b"\n") {
+                breaks -= 1;
+            }
+            chunks.push(chunk);
+            pos = start;
+        }
+
+        let mut tail: Vec<u8> = chunks.into_iter().rev().flatten().collect();
+        if pos > 0 {
+            // Drop the partial line at the start, which may also split a UTF-8 character.
+            let first = tail.iter().position(|&b| b == b'\n').map_or(0, |i| i + 1);
+            tail.drain(..first);
+        }
+        let tail = String::from_utf8(tail).map_err(|_| {
+            map_err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ))
+        })?;
+        let mut lines = tail.lines().map(String::from).collect::<Vec<_>>();
+        let skip = lines.len().saturating_sub(n);
+        Ok(lines.split_off(skip))
+    }
+
+    /// Open the file and read exactly `len` bytes starting at byte offset `start`.
+    ///
+    /// See [`FileRead::read_range`].
+    ///
+    /// [`FileRead::read_range`]: struct.FileRead.html#method.read_range
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("aaaabbbbcccc")?;
+    /// assert_eq!(b"bbbb", file.read_range(4, 4)?.as_slice());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_range(&self, start: u64, len: usize) -> Result<Vec<u8>> {
+        self.open_read()?.read_range(start, len)
+    }
+
+    /// Memory map the file read-only.
+    ///
+    /// The returned [`Mmap`] dereferences to `&[u8]`, so large files can be parsed in place
+    /// without copying them into memory first. The mapping stays valid after the file handle used
+    /// to create it is closed.
+    ///
+    /// > **Caveat:** the mapping reflects changes made to the file by other processes, so the
+    /// > bytes may change underneath a `&[u8]`, and if the file is truncated, accessing the
+    /// > removed part crashes the process (`SIGBUS` on Unix). Only map files which won't be
+    /// > modified while mapped.
+    ///
+    /// > This requires the `mmap` feature.
+    ///
+    /// [`Mmap`]: https://docs.rs/memmap2/0.9/memmap2/struct.Mmap.html
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.txt");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo bar")?;
+    ///
+    /// let map = file.mmap()?;
+    /// assert_eq!(b"foo bar", &map[..]);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub fn mmap(&self) -> Result<::memmap2::Mmap> {
+        let read = self.open_read()?;
+        // Safety: see the caveat above, the file must not be modified while mapped.
+        unsafe { ::memmap2::Mmap::map(&read.0.file) }
+            .map_err(|err| Error::new(err, "memory mapping", self.clone().into()))
+    }
+
+    /// Open the file and iterate over its lines along with their 1-based line numbers.
+    ///
+    /// This is a shortcut for [`FileRead::lines_indexed`], which is useful when reporting errors
+    /// that reference a line.
+    ///
+    /// [`FileRead::lines_indexed`]: struct.FileRead.html#method.lines_indexed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo\n\nbar")?;
+    ///
+    /// for line in file.lines_indexed()? {
+    ///     let (number, line) = line?;
+    ///     if line.is_empty() {
+    ///         assert_eq!(2, number);
+    ///     }
+    /// }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn lines_indexed(&self) -> Result<impl Iterator<Item = Result<(usize, String)>>> {
+        Ok(self.open_read()?.lines_indexed())
+    }
+
+    /// Iterate over the lines of the file, replacing invalid UTF-8 with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of failing.
+    ///
+    /// Lines are split on `\n` or `\r\n` like [`read_lines`], but the file is streamed, so one
+    /// bad byte in a huge log only affects its own line. As with [`read_string_lossy`], the
+    /// replaced data can't be recovered.
+    ///
+    /// [`read_lines`]: #method.read_lines
+    /// [`read_string_lossy`]: #method.read_string_lossy
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.log");
+    /// let file = PathFile::create(example)?;
+    /// std::fs::write(&file, b"ok\r\nbad \xFF byte\nlast")?;
+    ///
+    /// let lines = file.lines_lossy()?.collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(vec!["ok", "bad \u{FFFD} byte", "last"], lines);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn lines_lossy(&self) -> Result<impl Iterator<Item = Result<String>>> {
+        let mut reader = io::BufReader::new(self.open_read()?.0.file);
+        let path = self.clone();
+        let mut buf = Vec::new();
+        Ok(::std::iter::from_fn(move || {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) => None,
+                Ok(_) => {
+                    if buf.ends_with(b"\n") {
+                        buf.pop();
+                        if buf.ends_with(b"\r") {
+                            buf.pop();
+                        }
+                    }
+                    Some(Ok(String::from_utf8_lossy(&buf).into_owned()))
+                }
+                Err(err) => Some(Err(Error::new(err, "reading", path.clone().into()))),
+            }
+        }))
+    }
+
+    /// Read the entire contents of the file into a `String`, refusing files larger than
+    /// `max_bytes`.
+    ///
+    /// The size is checked with the file's metadata before anything is read, so this is safe to
+    /// call on untrusted files that may be huge. Returns `io::ErrorKind::InvalidInput` if the file
+    /// is too large.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo\nbar")?;
+    ///
+    /// assert_eq!("foo\nbar", file.read_string_limit(1024)?);
+    ///
+    /// let err = file.read_string_limit(4).unwrap_err();
+    /// assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidInput);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_string_limit(&self, max_bytes: u64) -> Result<String> {
+        let len = self.metadata()?.len();
+        if len > max_bytes {
+            return Err(Error::new(
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "file is {} bytes, more than the limit of {}",
+                        len, max_bytes
+                    ),
+                ),
+                "reading",
+                self.clone().into(),
+            ));
+        }
+        self.read_string()
+    }
+
+    /// Write the `str` to a file, truncating it first if it exists and creating it otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar";
+    /// file.write_str(expected)?;
+    /// assert_eq!(expected, file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_str(&self, s: &str) -> Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.create(true);
+        options.truncate(true);
+        let mut f = FileWrite::open_abs(self.clone(), options)?;
+        if s.is_empty() {
+            return Ok(());
+        }
+        f.write_str(s)?;
+        f.flush()
+    }
+
+    /// Write the `str` to a file like [`write_str`], returning the number of bytes written.
+    ///
+    /// [`write_str`]: #method.write_str
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathFile, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// assert_eq!(7, file.write_str_counted("foo\nbar")?);
+    /// assert_eq!(7, file.metadata()?.len());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_str_counted(&self, s: &str) -> Result<usize> {
+        // `write_str` writes the whole string or returns an error.
+        self.write_str(s)?;
+        Ok(s.len())
+    }
+
+    /// Write the `str` to a file as UTF-16 little endian with a leading byte order mark,
+    /// truncating it first if it exists and creating it otherwise.
+    ///
+    /// This is the encoding Windows tools usually mean by "Unicode". See [`read_utf16`] for an
+    /// example.
+    ///
+    /// [`read_utf16`]: #method.read_utf16
+    pub fn write_utf16_le(&self, s: &str) -> Result<()> {
+        let mut bytes = Vec::with_capacity(2 + s.len() * 2);
+        bytes.extend_from_slice(&[0xFF, 0xFE]);
+        for unit in s.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.create(true);
+        options.truncate(true);
+        let mut f = FileWrite::open_abs(self.clone(), options)?;
+        f.0.file
+            .write_all(&bytes)
+            .map_err(|err| Error::new(err, "writing", self.clone().into()))?;
+        f.flush()
+    }
+
+    /// Write each of the `lines` followed by a `\n` to a file, truncating it first if it exists
+    /// and creating it otherwise.
+    ///
+    /// The lines are buffered, so this is much faster than calling [`FileWrite::write_line`] for
+    /// each line.
+    ///
+    /// [`FileWrite::write_line`]: struct.FileWrite.html#method.write_line
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.txt");
+    /// let file = PathFile::create(example)?;
+    ///
+    /// file.write_lines(&["foo", "bar"])?;
+    /// assert_eq!("foo\nbar\n", file.read_string()?);
+    ///
+    /// file.write_lines((1..=3).map(|i| i.to_string()))?;
+    /// assert_eq!("1\n2\n3\n", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_lines<I, S>(&self, lines: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut options = fs::OpenOptions::new();
+        options.create(true);
+        options.truncate(true);
+        let mut f = FileWrite::open_abs(self.clone(), options)?;
+        let write_err = |err| Error::new(err, "writing", self.clone().into());
+        {
+            let mut buffered = io::BufWriter::new(&mut f.0.file);
+            for line in lines {
+                buffered
+                    .write_all(line.as_ref().as_bytes())
+                    .and_then(|_| buffered.write_all(b"\n"))
+                    .map_err(write_err)?;
+            }
+            buffered
+                .flush()
+                .map_err(|err| Error::new(err, "flushing", self.clone().into()))?;
+        }
+        f.flush()
+    }
+
+    /// Append the `str` to a file, creating it if it doesn't exist.
+    ///
+    /// > This opens the file on every call. When appending many times (i.e. logging), use
+    /// > [`append_writer`] or [`append_writer_buffered`] to keep a single handle open.
+    ///
+    /// [`append_writer`]: struct.PathFile.html#method.append_writer
+    /// [`append_writer_buffered`]: struct.PathFile.html#method.append_writer_buffered
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar\nbaz";
+    /// file.append_str("foo\nbar")?;
+    /// file.append_str("\nbaz")?;
+    /// assert_eq!(expected, file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn append_str(&self, s: &str) -> Result<()> {
+        let mut f = self.open_append()?;
+        if s.is_empty() {
+            return Ok(());
+        }
+        f.write_str(s)?;
+        f.flush()
+    }
+
+    /// Open the file as read-only.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io::Read;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar";
+    /// file.write_str(expected)?;
+    ///
+    /// let mut read = file.open_read()?;
+    /// let mut s = String::new();
+    /// read.read_to_string(&mut s)?;
+    /// assert_eq!(expected, s);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn open_read(&self) -> Result<FileRead> {
+        FileRead::open_abs(self.clone())
+    }
+
+    /// Open the file as read-only for async IO.
+    ///
+    /// > This requires the `async-tokio` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// # extern crate tokio;
+    /// use path_abs::PathFile;
+    ///
+    /// # async fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo")?;
+    ///
+    /// let mut read = file.open_read_async().await?;
+    /// assert_eq!("foo", read.read_string().await?);
+    /// # Ok(()) }
+    /// # fn main() {
+    /// #     let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #     rt.block_on(try_main()).unwrap()
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub async fn open_read_async(&self) -> Result<AsyncFileRead> {
+        AsyncFileRead::open_abs(self.clone()).await
+    }
+
+    /// Open the file as write-only for async IO, truncating it first.
+    ///
+    /// > This requires the `async-tokio` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// # extern crate tokio;
+    /// use path_abs::PathFile;
+    ///
+    /// # async fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("old contents")?;
+    ///
+    /// let mut write = file.open_write_async().await?;
+    /// write.write_str("new").await?;
+    /// write.flush().await?;
+    /// assert_eq!("new", file.read_string()?);
+    /// # Ok(()) }
+    /// # fn main() {
+    /// #     let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #     rt.block_on(try_main()).unwrap()
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub async fn open_write_async(&self) -> Result<AsyncFileWrite> {
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).truncate(true);
+        AsyncFileWrite::open_abs(self.clone(), options).await
+    }
+
+    /// Open the file as write-only in append mode.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io::Write;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar\n";
+    /// file.write_str("foo\n")?;
+    ///
+    /// let mut append = file.open_append()?;
+    /// append.write_all(b"bar\n")?;
+    /// append.flush();
+    /// assert_eq!(expected, file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn open_append(&self) -> Result<FileWrite> {
+        let mut options = fs::OpenOptions::new();
+        options.append(true);
+        FileWrite::open_abs(self.clone(), options)
+    }
+
+    /// Open the file for appending, returning a handle meant to be kept open for many appends.
+    ///
+    /// This is the same as [`open_append`], and should be preferred over calling [`append_str`]
+    /// repeatedly since that reopens the file every time.
+    ///
+    /// [`open_append`]: struct.PathFile.html#method.open_append
+    /// [`append_str`]: struct.PathFile.html#method.append_str
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.log";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let mut writer = file.append_writer()?;
+    /// for i in 0..3 {
+    ///     writer.write_str(&format!("line {}\n", i))?;
+    /// }
+    /// writer.flush()?;
+    /// assert_eq!("line 0\nline 1\nline 2\n", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn append_writer(&self) -> Result<FileWrite> {
+        self.open_append()
+    }
+
+    /// Open the file for appending, wrapping the handle in a `BufWriter` so that many small
+    /// appends result in few writes to the file.
+    ///
+    /// > Remember to `flush` the writer, otherwise errors which happen when it is dropped are
+    /// > ignored.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io::Write;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.log";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let mut writer = file.append_writer_buffered()?;
+    /// for i in 0..3 {
+    ///     writeln!(writer, "line {}", i)?;
+    /// }
+    /// writer.flush()?;
+    /// assert_eq!("line 0\nline 1\nline 2\n", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn append_writer_buffered(&self) -> Result<io::BufWriter<FileWrite>> {
+        Ok(io::BufWriter::new(self.open_append()?))
+    }
+
+    /// Open the file for editing (reading and writing).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io::{Read, Write};
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    ///
+    /// let expected = "foo\nbar";
+    ///
+    /// let mut edit = file.open_edit()?;
+    /// let mut s = String::new();
+    ///
+    /// edit.write_all(expected.as_bytes())?;
+    /// edit.rewind()?;
+    /// edit.read_to_string(&mut s)?;
+    /// assert_eq!(expected, s);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn open_edit(&self) -> Result<FileEdit> {
+        FileEdit::open_abs(self.clone(), fs::OpenOptions::new())
+    }
+
+    /// Open the file with arbitrary `OpenOptions`, for combinations the other `open_*` methods
+    /// can't express (e.g. reading and appending).
+    ///
+    /// Unlike [`FileEdit::open`], the `read` and `write` flags are used as given, so the caller
+    /// is responsible for setting sensible ones. Reading from or writing to a handle which
+    /// wasn't opened for it returns an error.
+    ///
+    /// [`FileEdit::open`]: struct.FileEdit.html#method.open
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::fs;
+    /// use std::io::{Read, Write};
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo\n")?;
+    ///
+    /// let mut options = fs::OpenOptions::new();
+    /// options.read(true).append(true);
+    /// let mut edit = file.open_with(options)?;
+    ///
+    /// let mut s = String::new();
+    /// edit.read_to_string(&mut s)?;
+    /// assert_eq!("foo\n", s);
+    ///
+    /// edit.write_all(b"bar\n")?;
+    /// assert_eq!("foo\nbar\n", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn open_with(&self, options: fs::OpenOptions) -> Result<FileEdit> {
+        Ok(FileEdit(FileOpen::open_abs(self.clone(), options)?))
     }
 
-    /// Instantiate a new `PathFile`, creating an empty file if it doesn't exist.
+    /// Reserve disk space so that the file is at least `size` bytes long, without truncating it.
+    ///
+    /// See [`FileWrite::allocate`] for details and platform support.
+    ///
+    /// [`FileWrite::allocate`]: struct.FileWrite.html#method.allocate
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use path_abs::PathFile;
+    /// use path_abs::{PathFile, PathInfo};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = "example.txt";
-    /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
-    ///
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
-    ///
+    /// # let example = &tmp.path().join("download.part");
     /// let file = PathFile::create(example)?;
-    ///
-    /// // It can be done twice with no effect.
-    /// let _ = PathFile::create(example)?;
+    /// file.preallocate(64 * 1024)?;
+    /// assert_eq!(64 * 1024, file.metadata()?.len());
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn create<P: AsRef<Path>>(path: P) -> Result<PathFile> {
-        fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(&path)
-            .map_err(|err| Error::new(err, "opening", path.as_ref().to_path_buf().into()))?;
-        PathFile::new(path)
+    pub fn preallocate(&self, size: u64) -> Result<()> {
+        FileWrite::open_abs(self.clone(), fs::OpenOptions::new())?.allocate(size)
     }
 
-    /// Read the entire contents of the file into a `String`.
+    /// Copy the file to another location, including permission bits
     ///
     /// # Examples
+    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
     /// use path_abs::PathFile;
+    /// use std::path::Path;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
     /// let example = "example.txt";
+    /// let example_bk = "example.txt.bk";
     /// # let tmp = tempfile::TempDir::new()?;
     /// # let example = &tmp.path().join(example);
+    /// # let example_bk = &tmp.path().join(example_bk);
     /// let file = PathFile::create(example)?;
     ///
-    /// let expected = "foo\nbar";
-    /// file.write_str(expected)?;
-    /// assert_eq!(expected, file.read_string()?);
+    /// let contents = "This is some contents";
+    /// file.write_str(contents);
+    /// let file_bk = file.copy(example_bk)?;
+    /// assert_eq!(contents, file.read_string()?);
+    /// assert_eq!(contents, file_bk.read_string()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn read_string(&self) -> Result<String> {
-        let mut f = self.open_read()?;
-        f.read_string()
+    pub fn copy<P: AsRef<Path>>(&self, path: P) -> Result<PathFile> {
+        fs::copy(&self, &path).map_err(|err| {
+            Error::new(
+                err,
+                &format!("copying {} from", path.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        Ok(PathFile::new(path)?)
     }
 
-    /// Write the `str` to a file, truncating it first if it exists and creating it otherwise.
+    /// Copy the file to another location, including permission bits and the access and
+    /// modification times.
+    ///
+    /// This is [`copy`] followed by setting the timestamps of the copy to those of `self`, which
+    /// keeps tools that compare modification times (build systems, backups) from seeing the copy
+    /// as new. Filesystems store timestamps with different granularity (e.g. FAT has a 2 second
+    /// resolution), so the copied times may be rounded and not compare exactly equal.
+    ///
+    /// [`copy`]: #method.copy
     ///
     /// # Examples
+    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use path_abs::PathFile;
+    /// use path_abs::{PathFile, PathInfo};
+    /// use std::fs;
+    /// use std::time::{Duration, SystemTime};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = "example.txt";
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
+    /// # let example = &tmp.path().join("example.txt");
+    /// # let example_bk = &tmp.path().join("example.txt.bk");
     /// let file = PathFile::create(example)?;
+    /// file.write_str("some contents")?;
+    /// let a_day_ago = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+    /// fs::File::options().write(true).open(&file)?.set_modified(a_day_ago)?;
     ///
-    /// let expected = "foo\nbar";
-    /// file.write_str(expected)?;
-    /// assert_eq!(expected, file.read_string()?);
+    /// let file_bk = file.copy_preserve(example_bk)?;
+    /// assert_eq!("some contents", file_bk.read_string()?);
+    /// assert_eq!(file.metadata()?.modified()?, file_bk.metadata()?.modified()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn write_str(&self, s: &str) -> Result<()> {
-        let mut options = fs::OpenOptions::new();
-        options.create(true);
-        options.truncate(true);
-        let mut f = FileWrite::open_abs(self.clone(), options)?;
-        if s.is_empty() {
-            return Ok(());
+    pub fn copy_preserve<P: AsRef<Path>>(&self, path: P) -> Result<PathFile> {
+        let metadata = self.metadata()?;
+        let copied = self.copy(path)?;
+        let mut times = fs::FileTimes::new();
+        if let Ok(accessed) = metadata.accessed() {
+            times = times.set_accessed(accessed);
         }
-        f.write_str(s)?;
-        f.flush()
+        if let Ok(modified) = metadata.modified() {
+            times = times.set_modified(modified);
+        }
+        fs::File::options()
+            .write(true)
+            .open(&copied)
+            .and_then(|f| f.set_times(times))
+            .map_err(|err| Error::new(err, "setting times of", copied.clone().into()))?;
+        Ok(copied)
     }
 
-    /// Append the `str` to a file, creating it if it doesn't exist.
+    /// Copy the file to another location without following it if it is a symlink.
+    ///
+    /// If `self` is a symlink, a new symlink pointing at the same target is created at `path`
+    /// (the link is copied, not the contents it points to). Otherwise this is the same as
+    /// [`copy`].
+    ///
+    /// > Relative link targets are copied verbatim, so they are resolved relative to the new
+    /// > location (like `cp -P`).
+    ///
+    /// [`copy`]: struct.PathFile.html#method.copy
     ///
     /// # Examples
+    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use path_abs::PathFile;
+    /// use path_abs::{PathFile, PathInfo};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
     /// let example = "example.txt";
+    /// let example_sym = "example.txt.sym";
+    /// let example_sym_bk = "example.txt.sym.bk";
     /// # let tmp = tempfile::TempDir::new()?;
     /// # let example = &tmp.path().join(example);
+    /// # let example_sym = &tmp.path().join(example_sym);
+    /// # let example_sym_bk = &tmp.path().join(example_sym_bk);
     /// let file = PathFile::create(example)?;
+    /// let file_sym = file.symlink(example_sym)?;
     ///
-    /// let expected = "foo\nbar\nbaz";
-    /// file.append_str("foo\nbar")?;
-    /// file.append_str("\nbaz")?;
-    /// assert_eq!(expected, file.read_string()?);
+    /// let file_sym_bk = file_sym.copy_link(example_sym_bk)?;
+    /// let ty = file_sym_bk.symlink_metadata()?.file_type();
+    /// assert!(ty.is_symlink());
+    /// assert_eq!(file, file_sym_bk.canonicalize()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn append_str(&self, s: &str) -> Result<()> {
-        let mut f = self.open_append()?;
-        if s.is_empty() {
-            return Ok(());
+    pub fn copy_link<P: AsRef<Path>>(&self, path: P) -> Result<PathFile> {
+        if !self.symlink_metadata()?.file_type().is_symlink() {
+            return self.copy(path);
         }
-        f.write_str(s)?;
-        f.flush()
+        let target = self.read_link()?;
+        symlink_file(&target, &path).map_err(|err| {
+            Error::new(
+                err,
+                &format!("copying link {} from", path.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        PathFile::new(path)
     }
 
-    /// Open the file as read-only.
+    /// Copy the file to another location like [`copy`], preserving holes in sparse files.
+    ///
+    /// A plain copy writes out every zero byte, expanding the holes of sparse files such as VM
+    /// images. Instead, this skips over runs of zeros in the destination so they become holes
+    /// (on filesystems that support them). On Linux the source's holes are found with
+    /// `SEEK_DATA`/`SEEK_HOLE` so they are not read either; elsewhere every block is read and
+    /// checked for zeros.
+    ///
+    /// [`copy`]: struct.PathFile.html#method.copy
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use std::io::Read;
-    /// use path_abs::PathFile;
+    /// use std::io::{Seek, SeekFrom, Write};
+    /// use path_abs::{PathFile, PathInfo};
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = "example.txt";
+    /// let example = "example.img";
+    /// let example_bk = "example.img.bk";
     /// # let tmp = tempfile::TempDir::new()?;
     /// # let example = &tmp.path().join(example);
+    /// # let example_bk = &tmp.path().join(example_bk);
     /// let file = PathFile::create(example)?;
+    /// let mut write = file.open_edit()?;
+    /// write.set_len(1 << 20)?;
+    /// write.seek(SeekFrom::End(-3))?;
+    /// write.write_all(b"end")?;
     ///
-    /// let expected = "foo\nbar";
-    /// file.write_str(expected)?;
-    ///
-    /// let mut read = file.open_read()?;
-    /// let mut s = String::new();
-    /// read.read_to_string(&mut s)?;
-    /// assert_eq!(expected, s);
+    /// let file_bk = file.copy_sparse(example_bk)?;
+    /// assert_eq!(1 << 20, file_bk.metadata()?.len());
+    /// assert!(file.same_contents(&file_bk)?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn open_read(&self) -> Result<FileRead> {
-        FileRead::open_abs(self.clone())
+    pub fn copy_sparse<P: AsRef<Path>>(&self, path: P) -> Result<PathFile> {
+        copy_sparse(self.as_path(), path.as_ref()).map_err(|err| {
+            Error::new(
+                err,
+                &format!("copying {} from", path.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        PathFile::new(path)
     }
 
-    /// Open the file as write-only in append mode.
+    /// Copy the file to another location, including permission bits, reporting progress as it
+    /// goes.
+    ///
+    /// `progress` is called with `(bytes_copied, total_bytes)` after each chunk is written, so it
+    /// can drive a progress bar for large files. `total_bytes` is the size of the file when the
+    /// copy started. Because the data is streamed through a buffer rather than using
+    /// [`copy`], platform accelerations such as reflinks are not used.
+    ///
+    /// [`copy`]: #method.copy
     ///
     /// # Examples
+    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use std::io::Write;
     /// use path_abs::PathFile;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = "example.txt";
     /// # let tmp = tempfile::TempDir::new()?;
-    /// # let example = &tmp.path().join(example);
+    /// # let example = &tmp.path().join("example.bin");
+    /// # let example_bk = &tmp.path().join("example.bin.bk");
     /// let file = PathFile::create(example)?;
+    /// file.write_str(&"x".repeat(200_000))?;
     ///
-    /// let expected = "foo\nbar\n";
-    /// file.write_str("foo\n")?;
-    ///
-    /// let mut append = file.open_append()?;
-    /// append.write_all(b"bar\n")?;
-    /// append.flush();
-    /// assert_eq!(expected, file.read_string()?);
+    /// let mut reports = Vec::new();
+    /// let file_bk = file.copy_with_progress(example_bk, |copied, total| {
+    ///     reports.push((copied, total));
+    /// })?;
+    /// assert!(reports.len() > 1);
+    /// assert_eq!(Some(&(200_000, 200_000)), reports.last());
+    /// assert_eq!(file.read_string()?, file_bk.read_string()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn open_append(&self) -> Result<FileWrite> {
-        let mut options = fs::OpenOptions::new();
-        options.append(true);
-        FileWrite::open_abs(self.clone(), options)
+    pub fn copy_with_progress<P, F>(&self, path: P, progress: F) -> Result<PathFile>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, u64),
+    {
+        copy_with_progress(self.as_path(), path.as_ref(), progress).map_err(|err| {
+            Error::new(
+                err,
+                &format!("copying {} from", path.as_ref().display()),
+                self.clone().into(),
+            )
+        })?;
+        PathFile::new(path)
     }
 
-    /// Open the file for editing (reading and writing).
+    /// Copy the file's contents to another location, passing them through `f` first.
+    ///
+    /// The destination is created if it does not exist and truncated otherwise. Unlike [`copy`],
+    /// permission bits are not copied.
+    ///
+    /// [`copy`]: struct.PathFile.html#method.copy
     ///
     /// # Examples
+    ///
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use std::io::{Read, Seek, Write, SeekFrom};
     /// use path_abs::PathFile;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
-    /// let example = "example.txt";
+    /// let example = "example.cfg";
+    /// let example_new = "example.new.cfg";
     /// # let tmp = tempfile::TempDir::new()?;
     /// # let example = &tmp.path().join(example);
+    /// # let example_new = &tmp.path().join(example_new);
     /// let file = PathFile::create(example)?;
+    /// file.write_str("verbose = yes\n")?;
     ///
-    /// let expected = "foo\nbar";
-    ///
-    /// let mut edit = file.open_edit()?;
-    /// let mut s = String::new();
-    ///
-    /// edit.write_all(expected.as_bytes())?;
-    /// edit.seek(SeekFrom::Start(0))?;
-    /// edit.read_to_string(&mut s)?;
-    /// assert_eq!(expected, s);
+    /// let migrated = file.copy_transform(example_new, |s| s.replace("yes", "true"))?;
+    /// assert_eq!("verbose = true\n", migrated.read_string()?);
+    /// assert_eq!("verbose = yes\n", file.read_string()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn open_edit(&self) -> Result<FileEdit> {
-        FileEdit::open_abs(self.clone(), fs::OpenOptions::new())
+    pub fn copy_transform<P, F>(&self, dst: P, f: F) -> Result<PathFile>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(String) -> String,
+    {
+        let contents = self.read_string()?;
+        let dst = PathFile::create(dst)?;
+        dst.write_str(&f(contents))?;
+        Ok(dst)
     }
 
-    /// Copy the file to another location, including permission bits
+    /// Rename a file, replacing the original file if `to` already exists.
     ///
-    /// # Examples
+    /// This will not work if the new name is on a different mount point.
     ///
+    /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
-    /// use path_abs::PathFile;
+    /// use path_abs::{PathFile, PathInfo};
     /// use std::path::Path;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
@@ -324,57 +1485,81 @@ impl PathFile {
     ///
     /// let contents = "This is some contents";
     /// file.write_str(contents);
-    /// let file_bk = file.copy(example_bk)?;
-    /// assert_eq!(contents, file.read_string()?);
+    /// let file_bk = file.clone().rename(example_bk)?;
+    /// assert!(!file.exists());
     /// assert_eq!(contents, file_bk.read_string()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn copy<P: AsRef<Path>>(&self, path: P) -> Result<PathFile> {
-        fs::copy(&self, &path).map_err(|err| {
+    pub fn rename<P: AsRef<Path>>(self, to: P) -> Result<PathFile> {
+        fs::rename(&self, &to).map_err(|err| {
             Error::new(
                 err,
-                &format!("copying {} from", path.as_ref().display()),
+                &format!("renaming to {} from", to.as_ref().display()),
                 self.clone().into(),
             )
         })?;
-        Ok(PathFile::new(path)?)
+        Ok(PathFile::new(to)?)
     }
 
-    /// Rename a file, replacing the original file if `to` already exists.
+    /// Move a file, replacing the original file if `to` already exists.
     ///
-    /// This will not work if the new name is on a different mount point.
+    /// This first attempts to [`rename`] the file. If that fails because `to` is on a different
+    /// mount point, it falls back to copying the file to `to` and then removing the original.
+    ///
+    /// > The fallback is **not atomic**: if removing the original fails after the copy succeeded,
+    /// > the file will exist in both locations.
+    ///
+    /// [`rename`]: struct.PathFile.html#method.rename
     ///
     /// # Examples
     /// ```rust
     /// # extern crate path_abs;
     /// # extern crate tempfile;
     /// use path_abs::{PathFile, PathInfo};
-    /// use std::path::Path;
     ///
     /// # fn try_main() -> ::std::io::Result<()> {
     /// let example = "example.txt";
-    /// let example_bk = "example.txt.bk";
+    /// let example_moved = "example_moved.txt";
     /// # let tmp = tempfile::TempDir::new()?;
     /// # let example = &tmp.path().join(example);
-    /// # let example_bk = &tmp.path().join(example_bk);
+    /// # let example_moved = &tmp.path().join(example_moved);
     /// let file = PathFile::create(example)?;
     ///
     /// let contents = "This is some contents";
     /// file.write_str(contents);
-    /// let file_bk = file.clone().rename(example_bk)?;
+    /// let file_moved = file.clone().move_to(example_moved)?;
     /// assert!(!file.exists());
-    /// assert_eq!(contents, file_bk.read_string()?);
+    /// assert_eq!(contents, file_moved.read_string()?);
     /// # Ok(()) } fn main() { try_main().unwrap() }
     /// ```
-    pub fn rename<P: AsRef<Path>>(self, to: P) -> Result<PathFile> {
-        fs::rename(&self, &to).map_err(|err| {
-            Error::new(
-                err,
-                &format!("renaming to {} from", to.as_ref().display()),
-                self.clone().into(),
-            )
-        })?;
-        Ok(PathFile::new(to)?)
+    pub fn move_to<P: AsRef<Path>>(self, to: P) -> Result<PathFile> {
+        match fs::rename(&self, &to) {
+            Ok(()) => {}
+            Err(ref err) if is_cross_device(err) => {
+                fs::copy(&self, &to).map_err(|err| {
+                    Error::new(
+                        err,
+                        &format!("moving to {} from", to.as_ref().display()),
+                        self.clone().into(),
+                    )
+                })?;
+                fs::remove_file(&self).map_err(|err| {
+                    Error::new(
+                        err,
+                        &format!("removing after moving to {}", to.as_ref().display()),
+                        self.clone().into(),
+                    )
+                })?;
+            }
+            Err(err) => {
+                return Err(Error::new(
+                    err,
+                    &format!("moving to {} from", to.as_ref().display()),
+                    self.into(),
+                ));
+            }
+        }
+        PathFile::new(to)
     }
 
     /// Creates a new symbolic link on the filesystem to the dst.
@@ -420,6 +1605,43 @@ impl PathFile {
         PathFile::new(dst)
     }
 
+    /// Return whether this file was modified more recently than `other`.
+    ///
+    /// This is the core of "rebuild if the source is newer than the output" checks. Both paths
+    /// must exist, an error is returned if either modification time can't be read.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::fs;
+    /// use std::time::{Duration, SystemTime};
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let source = &tmp.path().join("source.txt");
+    /// # let output = &tmp.path().join("output.txt");
+    /// let source = PathFile::create(source)?;
+    /// let output = PathFile::create(output)?;
+    ///
+    /// let an_hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+    /// fs::File::options().write(true).open(&output)?.set_modified(an_hour_ago)?;
+    ///
+    /// assert!(source.is_newer_than(&output)?);
+    /// assert!(!output.is_newer_than(&source)?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn is_newer_than<P: PathInfo + ?Sized>(&self, other: &P) -> Result<bool> {
+        fn modified<P: PathInfo + ?Sized>(path: &P) -> Result<SystemTime> {
+            path.metadata()?
+                .modified()
+                .map_err(|err| Error::new(err, "getting modified time of", path.to_arc_pathbuf()))
+        }
+
+        Ok(modified(self)? > modified(other)?)
+    }
+
     /// Remove (delete) the file from the filesystem, consuming self.
     ///
     /// # Examples
@@ -452,6 +1674,15 @@ impl PathFile {
         self.as_ref()
     }
 
+    /// Return the path as a `&str` if it is valid UTF-8, without allocating.
+    ///
+    /// See [`PathAbs::as_str`]
+    ///
+    /// [`PathAbs::as_str`]: struct.PathAbs.html#method.as_str
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+
     /// Returns the canonical form of the path with all intermediate components normalized and
     /// symbolic links resolved.
     ///
@@ -561,6 +1792,22 @@ impl PathOps for PathFile {
         Self::Output::new_unchecked(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.0.concat_all(parts)
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.0.join_all(parts)
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         self.0.with_file_name(file_name)
     }
@@ -568,19 +1815,146 @@ impl PathOps for PathFile {
     fn with_extension<S: AsRef<ffi::OsStr>>(&self, extension: S) -> Self::Output {
         self.0.with_extension(extension)
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        self.0.with_file_stem(stem)
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        self.0.normalized()
+    }
+}
+
+/// The size of the blocks checked for zeros by `copy_sparse`.
+const SPARSE_BLOCK: usize = 4096;
+
+/// Copy `src` to `dst`, seeking over zero blocks in `dst` instead of writing them.
+fn copy_sparse(src: &Path, dst: &Path) -> io::Result<()> {
+    let mut src_file = fs::File::open(src)?;
+    let metadata = src_file.metadata()?;
+    let len = metadata.len();
+    let mut dst_file = fs::File::create(dst)?;
+
+    let mut buf = vec![0; SPARSE_BLOCK];
+    for (start, end) in data_regions(&src_file, len)? {
+        src_file.seek(io::SeekFrom::Start(start))?;
+        let mut pos = start;
+        while pos < end {
+            let want = ::std::cmp::min(buf.len() as u64, end - pos) as usize;
+            let got = src_file.read(&mut buf[..want])?;
+            if got == 0 {
+                // The file was truncated while copying.
+                break;
+            }
+            let block = &buf[..got];
+            if block.iter().any(|&b| b != 0) {
+                dst_file.seek(io::SeekFrom::Start(pos))?;
+                dst_file.write_all(block)?;
+            }
+            pos += got as u64;
+        }
+    }
+
+    // Extend the file over any trailing hole.
+    dst_file.set_len(len)?;
+    dst_file.set_permissions(metadata.permissions())
+}
+
+/// The size of the chunks streamed by `copy_with_progress`.
+const PROGRESS_CHUNK: usize = 64 * 1024;
+
+/// Copy `src` to `dst` through a buffer, calling `progress` after each chunk.
+fn copy_with_progress<F>(src: &Path, dst: &Path, mut progress: F) -> io::Result<()>
+where
+    F: FnMut(u64, u64),
+{
+    let mut src_file = fs::File::open(src)?;
+    let metadata = src_file.metadata()?;
+    let total = metadata.len();
+    let mut dst_file = fs::File::create(dst)?;
+
+    let mut buf = vec![0; PROGRESS_CHUNK];
+    let mut copied = 0;
+    loop {
+        let got = match src_file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(got) => got,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        dst_file.write_all(&buf[..got])?;
+        copied += got as u64;
+        progress(copied, total);
+    }
+    dst_file.set_permissions(metadata.permissions())
+}
+
+/// The `(start, end)` byte ranges of `file` which may contain data, skipping its holes.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn data_regions(file: &fs::File, len: u64) -> io::Result<Vec<(u64, u64)>> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    let mut regions = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let data = unsafe { ::libc::lseek(fd, offset as ::libc::off_t, ::libc::SEEK_DATA) };
+        if data < 0 {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                // There is no more data after `offset`.
+                Some(::libc::ENXIO) => break,
+                // The filesystem doesn't support finding holes.
+                Some(::libc::EINVAL) if regions.is_empty() => return Ok(vec![(0, len)]),
+                _ => return Err(err),
+            }
+        }
+        let hole = unsafe { ::libc::lseek(fd, data, ::libc::SEEK_HOLE) };
+        if hole < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        regions.push((data as u64, hole as u64));
+        offset = hole as u64;
+    }
+    Ok(regions)
+}
+
+/// The `(start, end)` byte ranges of `file` which may contain data: all of it.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn data_regions(_file: &fs::File, len: u64) -> io::Result<Vec<(u64, u64)>> {
+    Ok(vec![(0, len)])
+}
+
+/// Whether the error was caused by trying to rename across mount points.
+fn is_cross_device(err: &io::Error) -> bool {
+    // `io::ErrorKind::CrossesDevices` would need a newer compiler, so check the raw codes.
+    #[cfg(unix)]
+    {
+        err.raw_os_error() == Some(::libc::EXDEV)
+    }
+    #[cfg(windows)]
+    {
+        const ERROR_NOT_SAME_DEVICE: i32 = 17;
+        err.raw_os_error() == Some(ERROR_NOT_SAME_DEVICE)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = err;
+        false
+    }
 }
 
 #[cfg(target_os = "wasi")]
-fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+pub(crate) fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
     std::os::wasi::fs::symlink_path(src, dst)
 }
 
 #[cfg(unix)]
-fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+pub(crate) fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
     ::std::os::unix::fs::symlink(src, dst)
 }
 
 #[cfg(windows)]
-fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+pub(crate) fn symlink_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
     ::std::os::windows::fs::symlink_file(src, dst)
 }
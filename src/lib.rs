@@ -65,6 +65,9 @@
 //! - [`PathType`](struct.PathType.html): an enum containing either a PathFile or a PathDir.
 //!   Returned by [`PathDir::list`][dir_list]
 //!
+//! There is also [`Relative`](struct.Relative.html): a relative path that is guaranteed not to
+//! escape the directory it is joined onto.
+//!
 //! In addition, all paths are serializable through serde (even on windows!) by using the crate
 //! [`stfu8`](https://crates.io/crates/stfu8) to encode/decode, allowing ill-formed UTF-16. See
 //! that crate for more details on how the resulting encoding can be edited (by hand) even in the
@@ -206,15 +209,30 @@
 #![cfg_attr(target_os = "wasi",
             feature(wasi_ext))]
 
+#[cfg(feature = "glob")]
+extern crate glob;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 #[cfg(feature = "serialize")]
 extern crate serde;
 #[macro_use]
 #[cfg(feature = "serialize")]
 extern crate serde_derive;
 
+#[cfg(feature = "digest")]
+extern crate sha2;
 #[cfg(feature = "serialize")]
 extern crate stfu8;
+#[cfg(feature = "async-tokio")]
+extern crate tokio;
+
+#[cfg(unix)]
+extern crate libc;
 
+#[cfg(test)]
+extern crate bincode;
 #[macro_use]
 #[cfg(test)]
 extern crate pretty_assertions;
@@ -234,27 +252,36 @@ use std::path::{self, Component, Components};
 use std_prelude::*;
 
 mod abs;
+#[cfg(feature = "async-tokio")]
+mod async_io;
 mod dir;
 mod edit;
 mod file;
 pub mod open;
 mod read;
+mod relative;
 #[cfg(feature = "serialize")]
 pub mod ser;
 mod ty;
 mod write;
 
-pub use crate::abs::PathAbs;
-pub use crate::dir::{ListDir, PathDir};
+pub use crate::abs::{Canonicalizer, PathAbs};
+#[cfg(feature = "async-tokio")]
+pub use crate::async_io::{AsyncFileRead, AsyncFileWrite};
+pub use crate::dir::{DirDiff, ListDir, PathDir, WalkDir};
 pub use crate::file::PathFile;
+pub use crate::relative::Relative;
 #[cfg(feature = "serialize")]
-pub use crate::ser::PathSer;
+pub use crate::ser::{FromStfu8, PathSer, ToStfu8};
 pub use crate::ty::PathType;
 
 pub use crate::edit::FileEdit;
 pub use crate::read::FileRead;
 pub use crate::write::FileWrite;
 
+#[cfg(feature = "mmap")]
+pub use memmap2::{Mmap, MmapMut};
+
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 /// An error produced by performing an filesystem operation on a `Path`.
@@ -325,6 +352,29 @@ impl Error {
         self.path.as_ref()
     }
 
+    /// Returns a cheap clone of the shared path associated with this error.
+    ///
+    /// This avoids copying the path into a new `PathBuf`, e.g. when retrying the operation or
+    /// building a new `Error` for the same path.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use std::io;
+    /// use path_abs::{Error, PathFile};
+    ///
+    /// let err = PathFile::new("/this/does/not/exist").unwrap_err();
+    /// let retry_err = Error::new(
+    ///     io::Error::new(io::ErrorKind::Other, "gave up"),
+    ///     "retrying",
+    ///     err.path_arc(),
+    /// );
+    /// assert_eq!(err.path(), retry_err.path());
+    /// ```
+    pub fn path_arc(&self) -> Arc<PathBuf> {
+        self.path.clone()
+    }
+
     /// Returns the `std::io::Error` associated with this errors.
     pub fn io_error(&self) -> &io::Error {
         &self.io_err
@@ -334,6 +384,69 @@ impl Error {
     pub fn action(&self) -> &str {
         &self.action
     }
+
+    /// Replace the action being performed when this error occured.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathFile;
+    ///
+    /// let err = PathFile::new("/this/does/not/exist").unwrap_err();
+    /// let err = err.with_action("loading config from");
+    /// assert_eq!("loading config from", err.action());
+    /// ```
+    pub fn with_action(mut self, action: &str) -> Error {
+        self.action = action.into();
+        self
+    }
+
+    /// Add higher level context in front of the action being performed, keeping the original.
+    ///
+    /// The context is separated from the action with a `", "`, so that the error displays as
+    /// e.g. `"... when loading config, resolving /etc/app.toml"`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathFile;
+    ///
+    /// let err = PathFile::new("/this/does/not/exist").unwrap_err();
+    /// let action = err.action().to_string();
+    ///
+    /// let err = err.context("loading config");
+    /// assert_eq!(format!("loading config, {}", action), err.action());
+    /// assert!(err.to_string().ends_with("when loading config, resolving /this/does/not/exist"));
+    /// ```
+    pub fn context(mut self, extra: &str) -> Error {
+        self.action = format!("{}, {}", extra, self.action);
+        self
+    }
+
+    /// Recover the `Error` stored inside an `io::Error`.
+    ///
+    /// Converting an `Error` into an `io::Error` keeps it as the inner error, so code that only
+    /// receives the `io::Error` can still get at the path and action. Returns `None` if the
+    /// `io::Error` did not come from this crate.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use std::io;
+    /// use path_abs::{Error, PathFile};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let io_err: io::Error = PathFile::new("/this/does/not/exist").unwrap_err().into();
+    ///
+    /// let err = Error::from_io(&io_err).expect("came from path_abs");
+    /// assert_eq!(err.path().to_str(), Some("/this/does/not/exist"));
+    ///
+    /// assert!(Error::from_io(&io::Error::new(io::ErrorKind::Other, "plain")).is_none());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn from_io(err: &io::Error) -> Option<&Error> {
+        err.get_ref()?.downcast_ref::<Error>()
+    }
 }
 
 impl error::Error for Error {
@@ -363,6 +476,17 @@ impl From<Error> for io::Error {
 /// [`path_abs::Error`] instead of a [`std::io::Error`] (although it will
 /// automatically convert into a `std::io::Error` with `?` if needed).
 ///
+/// Besides the `path_abs` types, this trait is implemented for `Path`, `PathBuf`, `str` and
+/// `OsStr`, so plain strings get the same rich errors without any wrapping:
+///
+/// ```rust
+/// # extern crate path_abs;
+/// use path_abs::PathInfo;
+///
+/// let err = "/this/does/not/exist.txt".metadata().unwrap_err();
+/// assert_eq!(err.action(), "getting metadata of");
+/// ```
+///
 /// [`path_abs::Error`]: struct.Error.html
 /// [`std::io::Error`]: https://doc.rust-lang.org/stable/std/io/struct.Error.html
 pub trait PathInfo {
@@ -425,10 +549,146 @@ pub trait PathInfo {
         Path::extension(self.as_path())
     }
 
+    /// Returns the extension as a `&str`, or `None` if there is no extension or it is not
+    /// valid unicode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert_eq!(Some("gz"), PathInfo::extension_str(Path::new("archive.tar.gz")));
+    /// assert_eq!(None, PathInfo::extension_str(Path::new("Makefile")));
+    /// ```
+    fn extension_str(&self) -> Option<&str> {
+        self.extension().and_then(ffi::OsStr::to_str)
+    }
+
+    /// Returns whether the path has the extension `ext` (given without the leading `.`).
+    ///
+    /// The comparison ignores ASCII case on Windows and macOS, where filesystems are
+    /// conventionally case-insensitive, and is exact everywhere else. Use
+    /// [`has_extension_case`] to choose explicitly.
+    ///
+    /// [`has_extension_case`]: trait.PathInfo.html#method.has_extension_case
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert!(PathInfo::has_extension(Path::new("src/lib.rs"), "rs"));
+    /// assert!(!PathInfo::has_extension(Path::new("src/lib.rs"), "toml"));
+    /// assert!(!PathInfo::has_extension(Path::new("Makefile"), ""));
+    /// ```
+    fn has_extension(&self, ext: &str) -> bool {
+        self.has_extension_case(ext, !cfg!(any(windows, target_os = "macos")))
+    }
+
+    /// Returns whether the path has the extension `ext`, comparing exactly when
+    /// `case_sensitive` is true and ignoring ASCII case otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use path_abs::PathInfo;
+    ///
+    /// let photo = Path::new("photo.JPG");
+    /// assert!(PathInfo::has_extension_case(photo, "jpg", false));
+    /// assert!(!PathInfo::has_extension_case(photo, "jpg", true));
+    /// assert!(PathInfo::has_extension_case(photo, "JPG", true));
+    /// ```
+    fn has_extension_case(&self, ext: &str, case_sensitive: bool) -> bool {
+        match self.extension_str() {
+            Some(actual) if case_sensitive => actual == ext,
+            Some(actual) => actual.eq_ignore_ascii_case(ext),
+            None => false,
+        }
+    }
+
+    /// Extracts the prefix of the file name: the portion before the _first_ `.`.
+    ///
+    /// This mirrors the (currently unstable) [`std::path::Path::file_prefix()`]. Like
+    /// [`file_stem`], a leading `.` is considered part of the name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::ffi::OsStr;
+    /// use std::path::Path;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert_eq!(Some(OsStr::new("archive")), PathInfo::file_prefix(Path::new("archive.tar.gz")));
+    /// assert_eq!(Some(OsStr::new("foo")), PathInfo::file_prefix(Path::new("foo.rs")));
+    /// assert_eq!(Some(OsStr::new(".bashrc")), PathInfo::file_prefix(Path::new(".bashrc")));
+    /// assert_eq!(Some(OsStr::new(".config")), PathInfo::file_prefix(Path::new(".config.toml")));
+    /// ```
+    ///
+    /// [`file_stem`]: trait.PathInfo.html#method.file_stem
+    /// [`std::path::Path::file_prefix()`]: https://doc.rust-lang.org/stable/std/path/struct.Path.html#method.file_prefix
+    fn file_prefix(&self) -> Option<&ffi::OsStr> {
+        // The same as std's `split_file_at_dot`.
+        let name = self.file_name()?;
+        let bytes = name.as_encoded_bytes();
+        if bytes == b".." {
+            return Some(name);
+        }
+        // Skip a leading `.`, which is part of the name.
+        match bytes[1..].iter().position(|&b| b == b'.') {
+            // SAFETY: the bytes are split just before an ASCII `.`, which is a valid boundary.
+            Some(i) => Some(unsafe { ffi::OsStr::from_encoded_bytes_unchecked(&bytes[..i + 1]) }),
+            None => Some(name),
+        }
+    }
+
     fn components(&self) -> Components<'_> {
         Path::components(self.as_path())
     }
 
+    /// Returns the number of normal (named) components in the path.
+    ///
+    /// Prefix, root, `.` and `..` components are not counted, so `/foo/bar` and `foo/./bar` both
+    /// have a depth of 2. Note that `..` does not cancel out the component before it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert_eq!(0, "/".depth());
+    /// assert_eq!(2, "/foo/bar".depth());
+    /// assert_eq!(2, "foo/./bar/".depth());
+    /// assert_eq!(1, "../foo".depth());
+    /// ```
+    fn depth(&self) -> usize {
+        self.components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .count()
+    }
+
+    /// Returns the number of components in the path, as yielded by [`components`].
+    ///
+    /// Unlike [`depth`], this counts prefix, root and `..` components.
+    ///
+    /// [`components`]: https://doc.rust-lang.org/stable/std/path/struct.Path.html#method.components
+    /// [`depth`]: trait.PathInfo.html#method.depth
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert_eq!(1, "/".component_count());
+    /// assert_eq!(3, "/foo/bar".component_count());
+    /// assert_eq!(2, "../foo".component_count());
+    /// ```
+    fn component_count(&self) -> usize {
+        self.components().count()
+    }
+
     fn iter(&self) -> path::Iter<'_> {
         Path::iter(self.as_path())
     }
@@ -500,6 +760,60 @@ pub trait PathInfo {
             .map_err(|err| Error::new(err, "canonicalizing", self.to_arc_pathbuf()))
     }
 
+    /// Returns whether this path is on the same filesystem as `other`.
+    ///
+    /// Renaming only works within a single filesystem, so this can be used to choose between
+    /// renaming and copying before attempting either. Both paths must exist.
+    ///
+    /// On Unix this compares the device ids of the two paths. On Windows it compares the volume
+    /// serial numbers, so mount points and junctions are handled. On other platforms an
+    /// `io::ErrorKind::Unsupported` error is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathInfo;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// assert!("src/lib.rs".same_filesystem_as("Cargo.toml")?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    fn same_filesystem_as<P: PathInfo + ?Sized>(&self, other: &P) -> Result<bool> {
+        Ok(filesystem_id(self)? == filesystem_id(other)?)
+    }
+
+    /// Returns whether the path is hidden by the platform's convention.
+    ///
+    /// On Windows this checks the `FILE_ATTRIBUTE_HIDDEN` attribute of the path (not following
+    /// symlinks), returning `false` if its metadata can't be read. Everywhere else a path is
+    /// hidden if its file name starts with a `.` ("dotfiles"), which requires no syscalls.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathInfo;
+    ///
+    /// assert!(!"src/lib.rs".is_hidden());
+    /// # #[cfg(unix)]
+    /// assert!("/home/user/.bashrc".is_hidden());
+    /// ```
+    fn is_hidden(&self) -> bool {
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::MetadataExt;
+            const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+            fs::symlink_metadata(self.as_path())
+                .map(|m| m.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+                .unwrap_or(false)
+        }
+        #[cfg(not(windows))]
+        {
+            self.file_name()
+                .map(|name| name.as_encoded_bytes().starts_with(b"."))
+                .unwrap_or(false)
+        }
+    }
+
     /// Returns the path without its final component, if there is one.
     ///
     /// The same as [`std::path::Path::parent()`], except that it returns a
@@ -521,18 +835,74 @@ pub trait PathInfo {
     }
 }
 
-// TODO: I would like to be able to do this.
-// impl<T> PathInfo for T
-// where
-//     T: AsRef<Path>
-// {
-//     fn as_path(&self) -> &Path {
-//         PathBuf::as_path(self.borrow())
-//     }
-//     fn to_arc_pathbuf(&self) -> Arc<PathBuf> {
-//         self.clone().into()
-//     }
-// }
+/// Identifies the filesystem (device) a path is on.
+#[cfg(unix)]
+fn filesystem_id<P: PathInfo + ?Sized>(path: &P) -> Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(path.metadata()?.dev())
+}
+
+/// Identifies the filesystem (volume) a path is on, using its volume serial number.
+#[cfg(windows)]
+fn filesystem_id<P: PathInfo + ?Sized>(path: &P) -> Result<u32> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+
+    #[repr(C)]
+    struct ByHandleFileInformation {
+        file_attributes: u32,
+        creation_time: [u32; 2],
+        last_access_time: [u32; 2],
+        last_write_time: [u32; 2],
+        volume_serial_number: u32,
+        file_size_high: u32,
+        file_size_low: u32,
+        number_of_links: u32,
+        file_index_high: u32,
+        file_index_low: u32,
+    }
+    // Needed to open a handle to a directory.
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetFileInformationByHandle(
+            file: *mut ::std::ffi::c_void,
+            info: *mut ByHandleFileInformation,
+        ) -> i32;
+    }
+
+    let action = "comparing filesystem of";
+    let file = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path.as_path())
+        .map_err(|err| Error::new(err, action, path.to_arc_pathbuf()))?;
+    let mut info: ByHandleFileInformation = unsafe { ::std::mem::zeroed() };
+    let ok = unsafe { GetFileInformationByHandle(file.as_raw_handle() as *mut _, &mut info) };
+    if ok == 0 {
+        return Err(Error::new(
+            io::Error::last_os_error(),
+            action,
+            path.to_arc_pathbuf(),
+        ));
+    }
+    Ok(info.volume_serial_number)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn filesystem_id<P: PathInfo + ?Sized>(path: &P) -> Result<()> {
+    Err(Error::new(
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "filesystem comparison is not supported on this platform",
+        ),
+        "comparing filesystem of",
+        path.to_arc_pathbuf(),
+    ))
+}
+
+// A blanket `impl<T: AsRef<Path>> PathInfo for T` would conflict with the impl below, so the
+// common unsized path-like types (`Path`, `str` and `OsStr`) are implemented directly instead.
 
 impl<T> PathInfo for T
 where
@@ -555,6 +925,85 @@ impl PathInfo for Path {
     }
 }
 
+impl PathInfo for str {
+    fn as_path(&self) -> &Path {
+        Path::new(self)
+    }
+    fn to_arc_pathbuf(&self) -> Arc<PathBuf> {
+        Arc::new(PathBuf::from(self))
+    }
+}
+
+impl PathInfo for ffi::OsStr {
+    fn as_path(&self) -> &Path {
+        Path::new(self)
+    }
+    fn to_arc_pathbuf(&self) -> Arc<PathBuf> {
+        Arc::new(PathBuf::from(self))
+    }
+}
+
+/// Normalize `path` lexically, without touching the filesystem.
+///
+/// `.` components are removed and each `..` consumes the component before it. Leading `..`
+/// components of a relative path are kept, since there is nothing for them to consume. Unlike
+/// [`PathAbs::new`] the path is not made absolute and symlinks are not resolved, so the result
+/// may not point to the same file as `path` if it traverses a symlink.
+///
+/// # Errors
+///
+/// Returns `io::ErrorKind::NotFound` if a `..` would go above the root of an absolute path.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use path_abs::normalize_lexically;
+///
+/// # fn try_main() -> ::std::io::Result<()> {
+/// assert_eq!(Path::new("foo/baz"), normalize_lexically("foo/./bar/../baz")?);
+/// assert_eq!(Path::new("../baz"), normalize_lexically("foo/../../baz")?);
+/// # if cfg!(unix) {
+/// assert_eq!(Path::new("/baz"), normalize_lexically("/foo/../baz")?);
+/// assert!(normalize_lexically("/..").is_err());
+/// # }
+/// # Ok(()) } fn main() { try_main().unwrap() }
+/// ```
+///
+/// [`PathAbs::new`]: struct.PathAbs.html#method.new
+pub fn normalize_lexically<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let mut res = PathBuf::new();
+    lexical_normalize(&mut res, path.components())
+        .map_err(|e| Error::new(e, "normalizing", path.to_path_buf().into()))?;
+    Ok(res)
+}
+
+/// Push `components` onto `res`, dropping `.` and letting each `..` consume the last component.
+///
+/// A `..` is kept when there is nothing left to consume in a relative path, and is an error when
+/// it would consume the root (or a prefix that implies a root).
+pub(crate) fn lexical_normalize<'a, I>(res: &mut PathBuf, components: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = Component<'a>>,
+{
+    for each in components {
+        match each {
+            Component::CurDir => (),
+            Component::ParentDir => match res.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    res.pop();
+                }
+                None | Some(Component::ParentDir) => res.push(".."),
+                Some(Component::Prefix(_)) if !res.has_root() => res.push(".."),
+                _ => return Err(io::Error::new(io::ErrorKind::NotFound, ".. consumed root")),
+            },
+            Component::Prefix(_) | Component::RootDir | Component::Normal(_) => res.push(each),
+        }
+    }
+    Ok(())
+}
+
 /// Methods that modify a path.
 ///
 /// These methods are not implemented for all `path_abs` types because they
@@ -673,6 +1122,63 @@ pub trait PathMut: PathInfo {
     fn set_file_name<S: AsRef<ffi::OsStr>>(&mut self, file_name: S);
 
     fn set_extension<S: AsRef<ffi::OsStr>>(&mut self, extension: S) -> bool;
+
+    /// Updates the file stem (the file name without its extension), preserving the extension.
+    ///
+    /// If there is no extension this is the same as [`set_file_name`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use path_abs::PathMut;
+    ///
+    /// let mut report = PathBuf::from("docs/report.pdf");
+    /// report.set_file_stem("summary");
+    /// assert_eq!(report, PathBuf::from("docs/summary.pdf"));
+    ///
+    /// let mut readme = PathBuf::from("docs/README");
+    /// readme.set_file_stem("CHANGELOG");
+    /// assert_eq!(readme, PathBuf::from("docs/CHANGELOG"));
+    /// ```
+    ///
+    /// [`set_file_name`]: https://doc.rust-lang.org/stable/std/path/struct.PathBuf.html#method.set_file_name
+    fn set_file_stem<S: AsRef<ffi::OsStr>>(&mut self, stem: S);
+
+    /// Collapse `.` and `..` components lexically, without touching the filesystem.
+    ///
+    /// `.` components are removed and each `..` consumes the component before it. Leading `..`
+    /// components of a relative path are kept, the same as [`pop_up`]. See
+    /// [`normalize_lexically`] for details.
+    ///
+    /// Returns an error, leaving the path unchanged, if a `..` would go above the root.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use path_abs::PathMut;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let mut path = PathBuf::from("foo/./bar/../baz");
+    /// path.normalize()?;
+    /// assert_eq!(path, PathBuf::from("foo/baz"));
+    ///
+    /// let mut path = PathBuf::from("foo/../../baz");
+    /// path.normalize()?;
+    /// assert_eq!(path, PathBuf::from("../baz"));
+    ///
+    /// # if cfg!(unix) {
+    /// let mut path = PathBuf::from("/foo/../..");
+    /// assert!(path.normalize().is_err());
+    /// assert_eq!(path, PathBuf::from("/foo/../.."));
+    /// # }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    ///
+    /// [`pop_up`]: trait.PathMut.html#tymethod.pop_up
+    /// [`normalize_lexically`]: fn.normalize_lexically.html
+    fn normalize(&mut self) -> Result<()>;
 }
 
 impl PathMut for PathBuf {
@@ -697,56 +1203,18 @@ impl PathMut for PathBuf {
     }
 
     fn pop_up(&mut self) -> Result<()> {
-        /// Pop off the parent components and return how
-        /// many were removed.
-        fn pop_parent_components(p: &mut PathBuf) -> usize {
-            let mut cur_dirs: usize = 0;
-            let mut parents: usize = 0;
-            let mut components = p.components();
-            while let Some(c) = components.next_back() {
-                match c {
-                    Component::CurDir => cur_dirs += 1,
-                    Component::ParentDir => parents += 1,
-                    _ => break,
-                }
-            }
-            for _ in 0..(cur_dirs + parents) {
-                p.pop();
-            }
-            parents
-        }
-
-        let mut ending_parents = 0;
-        loop {
-            ending_parents += pop_parent_components(self);
-            if ending_parents == 0 || self.file_name().is_none() {
-                break;
-            } else {
-                // we have at least one "parent" to consume
-                self.pop();
-                ending_parents -= 1;
-            }
-        }
-
-        if self.pop() {
-            // do nothing, success
-        } else if self.has_root() {
+        let mut res = PathBuf::new();
+        let components = self.components().chain(Some(Component::ParentDir));
+        if lexical_normalize(&mut res, components).is_err() {
             // We tried to pop off the root
             return Err(Error::new(
                 io::Error::new(io::ErrorKind::NotFound, "cannot get parent of root path"),
                 "truncating to parent",
                 self.clone().into(),
             ));
-        } else {
-            // we are creating a relative path, `"../"`
-            self.push("..")
-        }
-
-        // Put all unhandled parents back, creating a relative path.
-        for _ in 0..ending_parents {
-            self.push("..")
         }
 
+        *self = res;
         Ok(())
     }
 
@@ -772,6 +1240,23 @@ impl PathMut for PathBuf {
     fn set_extension<S: AsRef<ffi::OsStr>>(&mut self, extension: S) -> bool {
         self.set_extension(extension)
     }
+
+    fn set_file_stem<S: AsRef<ffi::OsStr>>(&mut self, stem: S) {
+        match Path::extension(self).map(ffi::OsStr::to_os_string) {
+            Some(extension) => {
+                let mut file_name = stem.as_ref().to_os_string();
+                file_name.push(".");
+                file_name.push(extension);
+                self.set_file_name(file_name)
+            }
+            None => self.set_file_name(stem),
+        }
+    }
+
+    fn normalize(&mut self) -> Result<()> {
+        *self = normalize_lexically(&*self)?;
+        Ok(())
+    }
 }
 
 impl PathMut for Arc<PathBuf> {
@@ -790,6 +1275,12 @@ impl PathMut for Arc<PathBuf> {
     fn set_extension<S: AsRef<ffi::OsStr>>(&mut self, extension: S) -> bool {
         Arc::make_mut(self).set_extension(extension)
     }
+    fn set_file_stem<S: AsRef<ffi::OsStr>>(&mut self, stem: S) {
+        Arc::make_mut(self).set_file_stem(stem)
+    }
+    fn normalize(&mut self) -> Result<()> {
+        Arc::make_mut(self).normalize()
+    }
 }
 
 /// Methods that return new path-like objects.
@@ -852,6 +1343,45 @@ pub trait PathOps: PathInfo {
     /// [`concat`]: trait.PathOps.html#method.concat
     fn join<P: AsRef<Path>>(&self, path: P) -> Self::Output;
 
+    /// Concatenate every path in `parts` onto `self`, in order.
+    ///
+    /// This is the same as chaining [`concat`] for each part, but works with any iterator of path
+    /// segments.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first part which fails to [`append`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::{Path, PathBuf};
+    /// use path_abs::PathOps;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let segments = vec!["foo".to_string(), "bar/../baz".to_string(), "qux.txt".to_string()];
+    /// let path = Path::new("/base").concat_all(&segments)?;
+    /// assert_eq!(path, PathBuf::from("/base/foo/baz/qux.txt"));
+    ///
+    /// assert!(Path::new("/base").concat_all(&["foo", "../../.."]).is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    ///
+    /// [`concat`]: trait.PathOps.html#method.concat
+    /// [`append`]: trait.PathMut.html#method.append
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>;
+
+    /// Join every path in `parts` onto `self`, in order, using [`join`].
+    ///
+    /// [`join`]: trait.PathOps.html#method.join
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>;
+
     /// Creates a new path object like `self` but with the given file name.
     ///
     /// The same as [`std::path::Path::with_file_name()`], except that the
@@ -867,6 +1397,35 @@ pub trait PathOps: PathInfo {
     ///
     /// [`std::path::Path::with_extension()`]: https://doc.rust-lang.org/stable/std/path/struct.Path.html#method.with_extension
     fn with_extension<S: AsRef<ffi::OsStr>>(&self, extension: S) -> Self::Output;
+
+    /// Creates a new path object like `self` but with the given file stem, preserving the
+    /// extension.
+    ///
+    /// See [`PathMut::set_file_stem`] for details. The return type depends on the trait
+    /// implementation.
+    ///
+    /// [`PathMut::set_file_stem`]: trait.PathMut.html#method.set_file_stem
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output;
+
+    /// Returns a copy of the path with `.` and `..` components collapsed lexically.
+    ///
+    /// See [`PathMut::normalize`] for details. The return type depends on the trait
+    /// implementation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use std::path::{Path, PathBuf};
+    /// use path_abs::PathOps;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let user_input = Path::new("./foo/bar/../baz");
+    /// assert_eq!(PathBuf::from("foo/baz"), user_input.normalized()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    ///
+    /// [`PathMut::normalize`]: trait.PathMut.html#tymethod.normalize
+    fn normalized(&self) -> Result<Self::Output>;
 }
 
 // impl<T> PathOps for T
@@ -908,6 +1467,30 @@ impl PathOps for Path {
         Path::join(self, path)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut res = self.to_owned();
+        for part in parts {
+            res.append(part)?;
+        }
+        Ok(res)
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut res = self.to_owned();
+        for part in parts {
+            res.push(part);
+        }
+        res
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         let mut res = self.to_owned();
         res.set_file_name(file_name);
@@ -919,6 +1502,16 @@ impl PathOps for Path {
         res.set_extension(extension);
         res
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        let mut res = self.to_owned();
+        res.set_file_stem(stem);
+        res
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        normalize_lexically(self)
+    }
 }
 
 impl PathOps for PathBuf {
@@ -932,6 +1525,22 @@ impl PathOps for PathBuf {
         Path::join(self, path)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.as_path().concat_all(parts)
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.as_path().join_all(parts)
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         self.as_path().with_file_name(file_name)
     }
@@ -939,6 +1548,14 @@ impl PathOps for PathBuf {
     fn with_extension<S: AsRef<ffi::OsStr>>(&self, extension: S) -> Self::Output {
         self.as_path().with_extension(extension)
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        PathOps::with_file_stem(self.as_path(), stem)
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        PathOps::normalized(self.as_path())
+    }
 }
 
 impl PathOps for Arc<PathBuf> {
@@ -955,6 +1572,29 @@ impl PathOps for Arc<PathBuf> {
         Arc::new(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut res = self.clone();
+        {
+            let buf = Arc::make_mut(&mut res);
+            for part in parts {
+                buf.append(part)?;
+            }
+        }
+        Ok(res)
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        Arc::new(self.as_path().join_all(parts))
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         let mut res = self.clone();
         Arc::make_mut(&mut res).set_file_name(file_name);
@@ -966,6 +1606,16 @@ impl PathOps for Arc<PathBuf> {
         Arc::make_mut(&mut res).set_extension(extension);
         res
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        let mut res = self.clone();
+        Arc::make_mut(&mut res).set_file_stem(stem);
+        res
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        Ok(Arc::new(PathOps::normalized(self.as_path())?))
+    }
 }
 
 #[cfg(test)]
@@ -992,6 +1642,17 @@ mod tests {
         regex::escape(&format!("{}", path.as_ref().display()))
     }
 
+    #[test]
+    fn file_prefix_matches_std() {
+        let prefix = |name| PathInfo::file_prefix(Path::new(name)).map(|p| p.to_str().unwrap());
+        assert_eq!(Some("."), prefix("..."));
+        assert_eq!(Some(".a"), prefix(".a.b"));
+        assert_eq!(Some("a"), prefix("a..b"));
+        assert_eq!(Some("a"), prefix("a."));
+        assert_eq!(None, prefix(".."));
+        assert_eq!(None, prefix("/"));
+    }
+
     #[test]
     /// Tests to make sure the error messages look like we expect.
     fn sanity_errors() {
@@ -1017,6 +1678,40 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    /// Every `canonicalize` wrapper reports the path it was called on, not where a link points.
+    fn canonicalize_errors_report_path() {
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let tmp_abs = PathDir::new(tmp_dir.path()).expect("tmp_abs");
+        let broken = tmp_abs.concat("broken").unwrap();
+        ::std::os::unix::fs::symlink(tmp_abs.concat("missing").unwrap(), &broken).unwrap();
+
+        let check = |err: Error| {
+            assert_eq!(err.io_error().kind(), io::ErrorKind::NotFound);
+            assert_eq!(err.action(), "canonicalizing");
+            assert_eq!(err.path(), broken.as_path());
+            assert_match!(format!(r"when canonicalizing {}$", escape(&broken)), err);
+        };
+        check(broken.canonicalize().unwrap_err());
+        check(
+            PathFile::new_unchecked(broken.clone())
+                .canonicalize()
+                .unwrap_err(),
+        );
+        check(
+            PathDir::new_unchecked(broken.clone())
+                .canonicalize()
+                .unwrap_err(),
+        );
+        check(
+            PathType::File(PathFile::new_unchecked(broken.clone()))
+                .canonicalize()
+                .unwrap_err(),
+        );
+        check(PathInfo::canonicalize(broken.as_path()).unwrap_err());
+    }
+
     #[cfg(test)]
     mod windows {
         use super::*;
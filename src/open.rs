@@ -81,6 +81,52 @@ impl FileOpen {
             path: self.path.clone(),
         })
     }
+
+    /// Read into `buf` starting at byte `offset`, using the platform's positioned read.
+    pub(crate) fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        #[cfg(unix)]
+        let res = {
+            use std::os::unix::fs::FileExt;
+            self.file.read_at(buf, offset)
+        };
+        #[cfg(windows)]
+        let res = {
+            use std::os::windows::fs::FileExt;
+            self.file.seek_read(buf, offset)
+        };
+        #[cfg(not(any(unix, windows)))]
+        let res = {
+            let _ = (buf, offset);
+            Err(::std::io::Error::new(
+                ::std::io::ErrorKind::Unsupported,
+                "positioned reads are not supported on this platform",
+            ))
+        };
+        res.map_err(|err| Error::new(err, "reading at offset", self.path.clone().into()))
+    }
+
+    /// Write `buf` starting at byte `offset`, using the platform's positioned write.
+    pub(crate) fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize> {
+        #[cfg(unix)]
+        let res = {
+            use std::os::unix::fs::FileExt;
+            self.file.write_at(buf, offset)
+        };
+        #[cfg(windows)]
+        let res = {
+            use std::os::windows::fs::FileExt;
+            self.file.seek_write(buf, offset)
+        };
+        #[cfg(not(any(unix, windows)))]
+        let res = {
+            let _ = (buf, offset);
+            Err(::std::io::Error::new(
+                ::std::io::ErrorKind::Unsupported,
+                "positioned writes are not supported on this platform",
+            ))
+        };
+        res.map_err(|err| Error::new(err, "writing at offset", self.path.clone().into()))
+    }
 }
 
 impl fmt::Debug for FileOpen {
@@ -10,6 +10,7 @@
 use std::fmt;
 use std::fs;
 use std::io;
+use std::io::BufRead;
 use std_prelude::*;
 
 use super::open::FileOpen;
@@ -62,13 +63,202 @@ impl FileRead {
     }
 
     /// Read what remains of the file to a `String`.
+    ///
+    /// If the contents are not valid UTF-8 the error is `io::ErrorKind::InvalidData` and its
+    /// message includes the byte offset (from where reading started) of the first invalid byte.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io::Write;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.open_append()?.write_all(b"abc\xffdef")?;
+    ///
+    /// let err = file.open_read()?.read_string().unwrap_err();
+    /// assert!(err.to_string().contains("offset 3"));
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
     pub fn read_string(&mut self) -> Result<String> {
-        let mut s = String::new();
+        let mut bytes = Vec::new();
         self.0
             .file
-            .read_to_string(&mut s)
+            .read_to_end(&mut bytes)
             .map_err(|err| Error::new(err, "reading", self.0.path.clone().into()))?;
-        Ok(s)
+        String::from_utf8(bytes).map_err(|err| {
+            let io_err = io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "stream did not contain valid UTF-8: invalid byte at offset {}",
+                    err.utf8_error().valid_up_to()
+                ),
+            );
+            Error::new(io_err, "reading", self.0.path.clone().into())
+        })
+    }
+
+    /// Read what remains of the file, appending it to `buf` and returning the number of bytes
+    /// read.
+    ///
+    /// This is [`Read::read_to_end`] with the new error type. Reading several files into the same
+    /// buffer avoids an allocation per file.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let foo = &tmp.path().join("foo.txt");
+    /// # let bar = &tmp.path().join("bar.txt");
+    /// let foo = PathFile::create(foo)?;
+    /// let bar = PathFile::create(bar)?;
+    /// foo.write_str("foo")?;
+    /// bar.write_str("bar")?;
+    ///
+    /// let mut buf = Vec::new();
+    /// for file in &[foo, bar] {
+    ///     file.open_read()?.read_to_end(&mut buf)?;
+    /// }
+    /// assert_eq!(b"foobar", buf.as_slice());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    ///
+    /// [`Read::read_to_end`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end
+    pub fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        self.0
+            .file
+            .read_to_end(buf)
+            .map_err(|err| Error::new(err, "reading", self.0.path.clone().into()))
+    }
+
+    /// Iterate over the remaining lines of the file along with their 1-based line numbers.
+    ///
+    /// Lines are split the same as [`BufRead::lines`]. Line numbers count from the current
+    /// position of the file, which is the start of the file unless it has been read from.
+    ///
+    /// [`BufRead::lines`]: https://doc.rust-lang.org/std/io/trait.BufRead.html#method.lines
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example.txt";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("foo\nbar\n")?;
+    ///
+    /// let lines = file.open_read()?.lines_indexed().collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(vec![(1, "foo".to_string()), (2, "bar".to_string())], lines);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn lines_indexed(self) -> impl Iterator<Item = Result<(usize, String)>> {
+        let FileOpen { path, file } = self.0;
+        io::BufReader::new(file)
+            .lines()
+            .enumerate()
+            .map(move |(i, line)| {
+                line.map(|line| (i + 1, line))
+                    .map_err(|err| Error::new(err, "reading", path.clone().into()))
+            })
+    }
+
+    /// Seek to the start of the file, the same as [`Seek::rewind`] but with the new error type.
+    ///
+    /// [`Seek::rewind`]: https://doc.rust-lang.org/std/io/trait.Seek.html#method.rewind
+    pub fn rewind(&mut self) -> Result<()> {
+        self.0
+            .file
+            .seek(io::SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|err| Error::new(err, "seeking", self.0.path.clone().into()))
+    }
+
+    /// Read exactly `len` bytes starting at byte offset `start`.
+    ///
+    /// The position of the file is left just after the range, so consecutive records can be read
+    /// with plain reads afterwards. If the file ends before `len` bytes could be read the error
+    /// is `io::ErrorKind::UnexpectedEof`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use std::io;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("aaaabbbbcccc")?;
+    ///
+    /// let mut read = file.open_read()?;
+    /// assert_eq!(b"bbbb", read.read_range(4, 4)?.as_slice());
+    /// assert_eq!("cccc", read.read_string()?);
+    ///
+    /// let err = read.read_range(8, 8).unwrap_err();
+    /// assert_eq!(io::ErrorKind::UnexpectedEof, err.io_error().kind());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_range(&mut self, start: u64, len: usize) -> Result<Vec<u8>> {
+        self.0
+            .file
+            .seek(io::SeekFrom::Start(start))
+            .map_err(|err| Error::new(err, "seeking", self.0.path.clone().into()))?;
+        let mut buf = vec![0; len];
+        self.0
+            .file
+            .read_exact(&mut buf)
+            .map_err(|err| Error::new(err, "reading", self.0.path.clone().into()))?;
+        Ok(buf)
+    }
+
+    /// Read into `buf` starting at byte `offset`, returning the number of bytes read.
+    ///
+    /// This takes `&self`, so several threads can read disjoint regions of the same file through
+    /// one handle. Like [`Read::read`] it may read fewer bytes than `buf` holds.
+    ///
+    /// On Unix this uses `pread`, which does not change the position of the file. On Windows
+    /// the position of the file is moved to just after the bytes read, so don't mix this with
+    /// plain reads on the same handle. On other platforms it returns `io::ErrorKind::Unsupported`.
+    ///
+    /// [`Read::read`]: https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("aaaabbbbcccc")?;
+    ///
+    /// let read = file.open_read()?;
+    /// let mut buf = [0; 4];
+    /// assert_eq!(4, read.read_at(&mut buf, 8)?);
+    /// assert_eq!(b"cccc", &buf);
+    /// assert_eq!(4, read.read_at(&mut buf, 4)?);
+    /// assert_eq!(b"bbbb", &buf);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize> {
+        self.0.read_at(buf, offset)
     }
 }
 
@@ -0,0 +1,157 @@
+/* Copyright (c) 2018 Garrett Berg, vitiral@gmail.com
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+//! Relative paths which cannot escape the directory they are joined onto.
+use std::ffi;
+use std::fmt;
+use std::io;
+use std::path::Component;
+use std_prelude::*;
+
+use super::{lexical_normalize, Error, Result};
+
+#[derive(Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+/// A relative path that is guaranteed not to escape the directory it is joined onto.
+///
+/// The path is resolved lexically at construction: it has no prefix, root or `.` components and
+/// every `..` has consumed a previous component. This makes it safe to join onto a directory
+/// when the path came from an untrusted source.
+pub struct Relative(pub(crate) Arc<PathBuf>);
+
+impl Relative {
+    /// Construct a validated relative path.
+    ///
+    /// `.` components are stripped and `..` components are resolved _semantically_ (not using
+    /// the file system), so `a/./b/../c` becomes `a/c`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `io::ErrorKind::InvalidInput` if the path has a prefix or root, or if a `..`
+    /// would go above the directory it is joined onto.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use path_abs::Relative;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let rel = Relative::new("foo/./bar/../baz")?;
+    /// assert_eq!(Path::new("foo/baz"), rel.as_path());
+    ///
+    /// assert!(Relative::new("foo/../../secret").is_err());
+    /// assert!(Relative::new("/etc/passwd").is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Relative> {
+        Relative::validate(path.as_ref(), io::ErrorKind::InvalidInput)
+    }
+
+    /// Validate `path` like [`new`], returning `escape_kind` if it would escape its base.
+    ///
+    /// [`new`]: #method.new
+    pub(crate) fn validate(path: &Path, escape_kind: io::ErrorKind) -> Result<Relative> {
+        let err = |kind, msg| {
+            Error::new(
+                io::Error::new(kind, msg),
+                "validating relative",
+                path.to_path_buf().into(),
+            )
+        };
+
+        if let Some(Component::Prefix(_)) | Some(Component::RootDir) = path.components().next() {
+            return Err(err(io::ErrorKind::InvalidInput, "path is not relative"));
+        }
+
+        let mut res = PathBuf::new();
+        lexical_normalize(&mut res, path.components())
+            .map_err(|e| Error::new(e, "validating relative", path.to_path_buf().into()))?;
+        if let Some(Component::ParentDir) = res.components().next() {
+            return Err(err(escape_kind, "path escapes its base"));
+        }
+        Ok(Relative(Arc::new(res)))
+    }
+
+    /// Create a `Relative` unchecked.
+    ///
+    /// This is mostly used for constructing during tests, or if the path was previously validated.
+    /// This is effectively the same as a `Arc<PathBuf>`.
+    ///
+    /// > Note: This is memory safe, so is not marked `unsafe`. However, joining it may escape
+    /// > the base directory if the path was not properly validated.
+    pub fn new_unchecked<P: Into<Arc<PathBuf>>>(path: P) -> Relative {
+        Relative(path.into())
+    }
+
+    /// Return a reference to a basic `std::path::Path`
+    pub fn as_path(&self) -> &Path {
+        self.as_ref()
+    }
+}
+
+impl fmt::Debug for Relative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<ffi::OsStr> for Relative {
+    fn as_ref(&self) -> &std::ffi::OsStr {
+        self.0.as_ref().as_ref()
+    }
+}
+
+impl AsRef<Path> for Relative {
+    fn as_ref(&self) -> &Path {
+        self.0.as_ref()
+    }
+}
+
+impl AsRef<PathBuf> for Relative {
+    fn as_ref(&self) -> &PathBuf {
+        self.0.as_ref()
+    }
+}
+
+impl Borrow<Path> for Relative {
+    fn borrow(&self) -> &Path {
+        self.as_ref()
+    }
+}
+
+impl Borrow<PathBuf> for Relative {
+    fn borrow(&self) -> &PathBuf {
+        self.as_ref()
+    }
+}
+
+impl Borrow<Path> for &Relative {
+    fn borrow(&self) -> &Path {
+        self.as_ref()
+    }
+}
+
+impl Borrow<PathBuf> for &Relative {
+    fn borrow(&self) -> &PathBuf {
+        self.as_ref()
+    }
+}
+
+impl From<Relative> for Arc<PathBuf> {
+    fn from(path: Relative) -> Arc<PathBuf> {
+        path.0
+    }
+}
+
+impl From<Relative> for PathBuf {
+    fn from(path: Relative) -> PathBuf {
+        match Arc::try_unwrap(path.0) {
+            Ok(p) => p,
+            Err(inner) => inner.as_ref().clone(),
+        }
+    }
+}
@@ -6,7 +6,9 @@
  * copied, modified, or distributed except according to those terms.
  */
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::fmt;
+use std::io;
 use std::string::ToString;
 use std_prelude::*;
 use stfu8;
@@ -21,7 +23,7 @@ use std::os::unix::ffi::{OsStrExt, OsStringExt};
 #[cfg(windows)]
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
 
-use super::{PathAbs, PathDir, PathFile};
+use super::{PathAbs, PathDir, PathFile, PathType};
 
 #[derive(Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct PathSer(Arc<PathBuf>);
@@ -42,6 +44,43 @@ impl PathSer {
     pub fn as_path(&self) -> &Path {
         self.as_ref()
     }
+
+    /// Validate the path as a `PathAbs`, see [`PathAbs::new`].
+    ///
+    /// This resolves the path lexically, so (like `PathAbs::new`) it may get the current
+    /// directory from the filesystem if the path is relative.
+    ///
+    /// [`PathAbs::new`]: ../struct.PathAbs.html#method.new
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::{PathAbs, PathSer};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let ser = PathSer::from("src/lib.rs");
+    /// assert_eq!(ser.to_abs()?, PathAbs::new("src/lib.rs")?);
+    /// assert!(ser.to_file().is_ok());
+    /// assert!(ser.to_dir().is_err());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn to_abs(&self) -> crate::Result<PathAbs> {
+        PathAbs::new(self)
+    }
+
+    /// Validate the path as an existing file, see [`PathFile::new`].
+    ///
+    /// [`PathFile::new`]: ../struct.PathFile.html#method.new
+    pub fn to_file(&self) -> crate::Result<PathFile> {
+        PathFile::new(self)
+    }
+
+    /// Validate the path as an existing directory, see [`PathDir::new`].
+    ///
+    /// [`PathDir::new`]: ../struct.PathDir.html#method.new
+    pub fn to_dir(&self) -> crate::Result<PathDir> {
+        PathDir::new(self)
+    }
 }
 
 impl fmt::Debug for PathSer {
@@ -66,6 +105,12 @@ impl PathMut for PathSer {
     fn set_extension<S: AsRef<OsStr>>(&mut self, extension: S) -> bool {
         self.0.set_extension(extension)
     }
+    fn set_file_stem<S: AsRef<OsStr>>(&mut self, stem: S) {
+        self.0.set_file_stem(stem)
+    }
+    fn normalize(&mut self) -> crate::Result<()> {
+        self.0.normalize()
+    }
 }
 
 impl PathOps for PathSer {
@@ -80,6 +125,22 @@ impl PathOps for PathSer {
         Self::Output::new(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> crate::Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        Ok(PathSer(self.0.concat_all(parts)?))
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        PathSer(self.0.join_all(parts))
+    }
+
     fn with_file_name<S: AsRef<OsStr>>(&self, file_name: S) -> Self::Output {
         PathSer(self.0.with_file_name(file_name))
     }
@@ -87,6 +148,14 @@ impl PathOps for PathSer {
     fn with_extension<S: AsRef<OsStr>>(&self, extension: S) -> Self::Output {
         PathSer(self.0.with_extension(extension))
     }
+
+    fn with_file_stem<S: AsRef<OsStr>>(&self, stem: S) -> Self::Output {
+        PathSer(self.0.with_file_stem(stem))
+    }
+
+    fn normalized(&self) -> crate::Result<Self::Output> {
+        Ok(PathSer(self.0.normalized()?))
+    }
 }
 
 impl AsRef<OsStr> for PathSer {
@@ -131,9 +200,57 @@ impl<'a> Borrow<PathBuf> for &'a PathSer {
     }
 }
 
-impl<P: Into<PathBuf>> From<P> for PathSer {
-    fn from(path: P) -> PathSer {
-        PathSer::new(path.into())
+impl<T: ?Sized + AsRef<OsStr>> From<&T> for PathSer {
+    fn from(path: &T) -> PathSer {
+        PathSer::new(PathBuf::from(path))
+    }
+}
+
+macro_rules! path_ser_from {
+    ($ty:ty) => {
+        impl From<$ty> for PathSer {
+            fn from(path: $ty) -> PathSer {
+                PathSer::new(PathBuf::from(path))
+            }
+        }
+    };
+}
+
+path_ser_from!(PathBuf);
+path_ser_from!(String);
+path_ser_from!(OsString);
+path_ser_from!(Box<Path>);
+path_ser_from!(Cow<'_, Path>);
+
+impl From<Arc<PathBuf>> for PathSer {
+    fn from(path: Arc<PathBuf>) -> PathSer {
+        PathSer(path)
+    }
+}
+
+// The `path_abs` types already hold an `Arc<PathBuf>`, so converting them does not copy the path.
+
+impl From<PathAbs> for PathSer {
+    fn from(path: PathAbs) -> PathSer {
+        PathSer(path.0)
+    }
+}
+
+impl From<PathFile> for PathSer {
+    fn from(path: PathFile) -> PathSer {
+        PathSer(path.into())
+    }
+}
+
+impl From<PathDir> for PathSer {
+    fn from(path: PathDir) -> PathSer {
+        PathSer(path.into())
+    }
+}
+
+impl From<PathType> for PathSer {
+    fn from(path: PathType) -> PathSer {
+        PathSer(path.into())
     }
 }
 
@@ -143,12 +260,6 @@ impl From<PathSer> for Arc<PathBuf> {
     }
 }
 
-// impl From<PathAbs> for PathSer {
-//     fn from(path: PathAbs) -> PathSer {
-//         PathSer(path.0)
-//     }
-// }
-
 impl<T> ToStfu8 for T
 where
     T: Borrow<PathBuf>,
@@ -187,6 +298,126 @@ where
     }
 }
 
+/// The raw OS representation of a path, used by non-human-readable formats.
+#[cfg(any(target_os = "wasi", unix))]
+fn to_raw_bytes(path: &Path) -> Vec<u8> {
+    path.as_os_str().as_bytes().to_vec()
+}
+
+/// The raw OS representation of a path, used by non-human-readable formats.
+///
+/// Each `u16` of the wide string is stored as two little-endian bytes.
+#[cfg(windows)]
+fn to_raw_bytes(path: &Path) -> Vec<u8> {
+    path.as_os_str()
+        .encode_wide()
+        .flat_map(|c| c.to_le_bytes().to_vec())
+        .collect()
+}
+
+#[cfg(any(target_os = "wasi", unix))]
+fn from_raw_bytes(bytes: Vec<u8>) -> Result<PathBuf, String> {
+    Ok(OsString::from_vec(bytes).into())
+}
+
+#[cfg(windows)]
+fn from_raw_bytes(bytes: Vec<u8>) -> Result<PathBuf, String> {
+    if bytes.len() % 2 != 0 {
+        return Err(format!(
+            "expected an even number of bytes for a wide path, got {}",
+            bytes.len()
+        ));
+    }
+    let wide: Vec<u16> = bytes
+        .chunks(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok(OsString::from_wide(&wide).into())
+}
+
+/// Visitor accepting the raw bytes written by `to_raw_bytes`.
+struct RawBytesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RawBytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the raw bytes of a path")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Serialize a path as an STFU8 string for human-readable formats and as raw OS bytes otherwise.
+fn serialize_path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&path.to_path_buf().to_stfu8())
+    } else {
+        serializer.serialize_bytes(&to_raw_bytes(path))
+    }
+}
+
+/// The inverse of `serialize_path`.
+fn deserialize_path<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        PathBuf::from_stfu8(&s).map_err(|err| serde::de::Error::custom(&err.to_string()))
+    } else {
+        let bytes = deserializer.deserialize_byte_buf(RawBytesVisitor)?;
+        from_raw_bytes(bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Decode an STFU8 string into a path, wrapping the decode error in a rich `Error`.
+fn decode_stfu8(s: &str) -> crate::Result<PathBuf> {
+    PathBuf::from_stfu8(s).map_err(|err| {
+        crate::Error::new(
+            io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+            "decoding stfu8",
+            Arc::new(PathBuf::from(s)),
+        )
+    })
+}
+
+macro_rules! stfu8_methods {
+    ($name:ident, $construct:expr) => {
+        impl $name {
+            /// Encode the path as [STFU8](https://crates.io/crates/stfu8), the same encoding used
+            /// when serializing it.
+            pub fn to_stfu8(&self) -> String {
+                ToStfu8::to_stfu8(self)
+            }
+
+            /// Decode a path from [STFU8](https://crates.io/crates/stfu8), validating it the same
+            /// way as the type's `new` constructor.
+            ///
+            /// Returns `io::ErrorKind::InvalidData` if the string is not valid STFU8.
+            pub fn from_stfu8(s: &str) -> crate::Result<$name> {
+                $construct(decode_stfu8(s)?)
+            }
+        }
+    };
+}
+
+stfu8_methods!(PathAbs, PathAbs::new);
+stfu8_methods!(PathFile, PathFile::new);
+stfu8_methods!(PathDir, PathDir::new);
+stfu8_methods!(PathSer, |path: PathBuf| Ok(PathSer::new(path)));
+
 macro_rules! stfu8_serialize {
     ($name:ident) => {
         impl Serialize for $name {
@@ -194,7 +425,7 @@ macro_rules! stfu8_serialize {
             where
                 S: Serializer,
             {
-                serializer.serialize_str(&self.to_stfu8())
+                serialize_path(self.as_path(), serializer)
             }
         }
     };
@@ -210,9 +441,7 @@ impl<'de> Deserialize<'de> for PathSer {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let path =
-            PathBuf::from_stfu8(&s).map_err(|err| serde::de::Error::custom(&err.to_string()))?;
+        let path = deserialize_path(deserializer)?;
         Ok(PathSer(Arc::new(path)))
     }
 }
@@ -222,9 +451,7 @@ impl<'de> Deserialize<'de> for PathAbs {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let path =
-            PathBuf::from_stfu8(&s).map_err(|err| serde::de::Error::custom(&err.to_string()))?;
+        let path = deserialize_path(deserializer)?;
         Ok(PathAbs(Arc::new(path)))
     }
 }
@@ -249,6 +476,57 @@ impl<'de> Deserialize<'de> for PathDir {
     }
 }
 
+/// Serialize a `PathType` as just its path, without the `type`/`path` wrapper.
+///
+/// Use it with `#[serde(with = "path_abs::ser::untagged")]`. Deserializing probes the filesystem
+/// the same way as [`PathType::new`], so the path must exist.
+///
+/// ```rust
+/// # extern crate path_abs;
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde_json;
+///
+/// use path_abs::{PathInfo, PathType};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Manifest {
+///     #[serde(with = "path_abs::ser::untagged")]
+///     entry: PathType,
+/// }
+///
+/// # fn try_main() -> ::std::io::Result<()> {
+/// let manifest = Manifest { entry: PathType::new("src")? };
+/// let json = serde_json::to_string(&manifest)?;
+/// assert!(!json.contains("\"type\""));
+///
+/// let manifest: Manifest = serde_json::from_str(&json)?;
+/// assert!(manifest.entry.is_dir());
+/// # Ok(()) } fn main() { try_main().unwrap() }
+/// ```
+///
+/// [`PathType::new`]: ../enum.PathType.html#method.new
+pub mod untagged {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::super::{PathAbs, PathInfo, PathType};
+
+    pub fn serialize<S>(path: &PathType, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize_path(path.as_path(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PathType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let abs = PathAbs::deserialize(deserializer)?;
+        PathType::try_from(abs).map_err(|err| serde::de::Error::custom(&err.to_string()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::{PathDir, PathFile, PathInfo, PathMut, PathOps, PathType};
@@ -306,6 +584,63 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn binary_raw_bytes() {
+        use bincode;
+
+        let path = PathSer::from("some/example path.txt");
+        let encoded = bincode::serialize(&path).unwrap();
+        // bincode prefixes the bytes with a u64 length.
+        assert_eq!(to_raw_bytes(path.as_path()), &encoded[8..]);
+
+        let decoded: PathSer = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(path, decoded);
+
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, "\"some/example path.txt\"");
+    }
+
+    #[test]
+    fn from_typed_paths() {
+        use serde_json;
+        use tempfile::TempDir;
+
+        let tmp_dir = TempDir::new().expect("create temp dir");
+        let dir = PathDir::new(tmp_dir.path()).expect("tmp_abs");
+        let file = PathFile::create(dir.concat("foo.txt").unwrap()).expect("foo.txt");
+        let abs = PathAbs::from(file.clone());
+        let ty = PathType::Dir(dir.clone());
+
+        // No copy of the underlying buffer is made.
+        let ser = PathSer::from(abs.clone());
+        assert!(Arc::ptr_eq(&abs.0, &ser.0));
+
+        for (ser, expected) in [
+            (ser, abs.as_path()),
+            (PathSer::from(file.clone()), file.as_path()),
+            (PathSer::from(dir.clone()), dir.as_path()),
+            (PathSer::from(ty.clone()), ty.as_path()),
+        ] {
+            let json = serde_json::to_string(&ser).unwrap();
+            let result: PathSer = serde_json::from_str(&json).unwrap();
+            assert_eq!(result.as_path(), expected);
+        }
+    }
+
+    #[test]
+    fn stfu8_methods() {
+        let ser = PathSer::from("example/path");
+        assert_eq!(ser.to_stfu8(), "example/path");
+        assert_eq!(PathSer::from_stfu8("example/path").unwrap(), ser);
+
+        let lib = PathFile::new("src/lib.rs").unwrap();
+        assert_eq!(PathFile::from_stfu8(&lib.to_stfu8()).unwrap(), lib);
+
+        let err = PathSer::from_stfu8(r"bad \x escape").unwrap_err();
+        assert_eq!(err.io_error().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.action(), "decoding stfu8");
+    }
+
     #[test]
     /// Just test that it has all the methods.
     fn sanity_ser() {
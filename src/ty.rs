@@ -6,6 +6,7 @@
  * copied, modified, or distributed except according to those terms.
  */
 use std::ffi;
+use std::fs;
 use std_prelude::*;
 
 use super::Result;
@@ -117,6 +118,152 @@ impl PathType {
             false
         }
     }
+
+    /// Rename the file or directory, returning the renamed path as the same variant.
+    ///
+    /// This will not work if the new name is on a different mount point.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathInfo, PathOps, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let example_dir = PathDir::create(example)?;
+    /// PathDir::create(example_dir.concat("foo")?)?;
+    /// PathFile::create(example_dir.concat("bar.txt")?)?;
+    ///
+    /// let entries = example_dir.list()?.collect::<Result<Vec<PathType>, _>>()?;
+    /// for entry in entries {
+    ///     let renamed = entry.clone().rename(entry.with_extension("bk"))?;
+    ///     assert_eq!(entry.is_dir(), renamed.is_dir());
+    ///     assert!(!entry.exists());
+    /// }
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn rename<P: AsRef<Path>>(self, to: P) -> Result<PathType> {
+        match self {
+            PathType::File(p) => Ok(PathType::File(p.rename(to)?)),
+            PathType::Dir(p) => Ok(PathType::Dir(p.rename(to)?)),
+        }
+    }
+
+    /// Remove (delete) the file or _empty_ directory from the filesystem, consuming self.
+    ///
+    /// Files are removed with [`PathFile::remove`] and directories with [`PathDir::remove`], which
+    /// returns an error if the directory is not empty. Use [`remove_all`] to also remove the
+    /// contents of a directory.
+    ///
+    /// [`PathFile::remove`]: struct.PathFile.html#method.remove
+    /// [`PathDir::remove`]: struct.PathDir.html#method.remove
+    /// [`remove_all`]: #method.remove_all
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathOps, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let example_dir = PathDir::create(example)?;
+    /// let file = PathFile::create(example_dir.concat("bar.txt")?)?;
+    ///
+    /// assert!(PathType::Dir(example_dir.clone()).remove().is_err());
+    /// PathType::File(file).remove()?;
+    /// PathType::Dir(example_dir).remove()?;
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn remove(self) -> Result<()> {
+        match self {
+            PathType::File(p) => p.remove(),
+            PathType::Dir(p) => p.remove(),
+        }
+    }
+
+    /// Remove (delete) the file, or the directory after recursively removing its contents,
+    /// consuming self. Use carefully!
+    ///
+    /// Files are removed with [`PathFile::remove`] and directories with [`PathDir::remove_all`].
+    ///
+    /// [`PathFile::remove`]: struct.PathFile.html#method.remove
+    /// [`PathDir::remove_all`]: struct.PathDir.html#method.remove_all
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{PathDir, PathFile, PathInfo, PathOps, PathType};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let example = "example";
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join(example);
+    /// let example_dir = PathDir::create(example)?;
+    /// PathFile::create(example_dir.concat("bar.txt")?)?;
+    ///
+    /// PathType::Dir(example_dir.clone()).remove_all()?;
+    /// assert!(!example_dir.exists());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn remove_all(self) -> Result<()> {
+        match self {
+            PathType::File(p) => p.remove(),
+            PathType::Dir(p) => p.remove_all(),
+        }
+    }
+
+    /// Queries the file system to get information about the file or directory.
+    ///
+    /// This is the same as [`PathInfo::metadata`], but doesn't require the trait to be imported.
+    ///
+    /// [`PathInfo::metadata`]: trait.PathInfo.html#method.metadata
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathType;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// assert!(PathType::new("src")?.metadata()?.is_dir());
+    /// assert!(PathType::new("src/lib.rs")?.metadata()?.is_file());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn metadata(&self) -> Result<fs::Metadata> {
+        match self {
+            PathType::File(p) => PathInfo::metadata(p),
+            PathType::Dir(p) => PathInfo::metadata(p),
+        }
+    }
+
+    /// Returns the canonical form of the path, preserving the variant.
+    ///
+    /// See [`PathAbs::canonicalize`]
+    ///
+    /// [`PathAbs::canonicalize`]: struct.PathAbs.html#method.canonicalize
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// use path_abs::PathType;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// let lib = PathType::new("src/../src/lib.rs")?.canonicalize()?;
+    /// assert_eq!(PathType::new("src/lib.rs")?, lib);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn canonicalize(&self) -> Result<PathType> {
+        match self {
+            PathType::File(p) => Ok(PathType::File(p.canonicalize()?)),
+            PathType::Dir(p) => Ok(PathType::Dir(p.canonicalize()?)),
+        }
+    }
 }
 
 impl AsRef<ffi::OsStr> for PathType {
@@ -222,6 +369,28 @@ impl PathOps for PathType {
         Self::Output::new_unchecked(buf)
     }
 
+    fn concat_all<I, P>(&self, parts: I) -> Result<Self::Output>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        match self {
+            PathType::File(p) => p.concat_all(parts),
+            PathType::Dir(p) => p.concat_all(parts),
+        }
+    }
+
+    fn join_all<I, P>(&self, parts: I) -> Self::Output
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        match self {
+            PathType::File(p) => p.join_all(parts),
+            PathType::Dir(p) => p.join_all(parts),
+        }
+    }
+
     fn with_file_name<S: AsRef<ffi::OsStr>>(&self, file_name: S) -> Self::Output {
         match self {
             PathType::File(p) => p.with_file_name(file_name),
@@ -235,4 +404,18 @@ impl PathOps for PathType {
             PathType::Dir(p) => p.with_extension(extension),
         }
     }
+
+    fn with_file_stem<S: AsRef<ffi::OsStr>>(&self, stem: S) -> Self::Output {
+        match self {
+            PathType::File(p) => p.with_file_stem(stem),
+            PathType::Dir(p) => p.with_file_stem(stem),
+        }
+    }
+
+    fn normalized(&self) -> Result<Self::Output> {
+        match self {
+            PathType::File(p) => p.normalized(),
+            PathType::Dir(p) => p.normalized(),
+        }
+    }
 }
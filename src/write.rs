@@ -131,6 +131,42 @@ impl FileWrite {
             .map_err(|err| Error::new(err, "setting len for", self.0.path.clone().into()))
     }
 
+    /// Reserve disk space so that the file is at least `size` bytes long.
+    ///
+    /// Unlike [`set_len`], the space is actually allocated, so running out of disk space is
+    /// detected now (as `ENOSPC` or similar) instead of part way through writing, and the file is
+    /// less likely to be fragmented. The file is never shrunk.
+    ///
+    /// This uses `posix_fallocate` on Linux and Android, `F_PREALLOCATE` on macOS and iOS and
+    /// `SetFileInformationByHandle` on Windows. Elsewhere, or if the filesystem doesn't support
+    /// preallocation, it falls back to extending the file with [`set_len`], which usually creates
+    /// a sparse file and doesn't reserve anything.
+    ///
+    /// [`set_len`]: #method.set_len
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{FileWrite, PathFile, PathInfo};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let mut write = FileWrite::create(example)?;
+    /// write.allocate(1024 * 1024)?;
+    /// assert_eq!(1024 * 1024, write.path().metadata()?.len());
+    ///
+    /// // It never shrinks the file.
+    /// write.allocate(16)?;
+    /// assert_eq!(1024 * 1024, write.path().metadata()?.len());
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn allocate(&mut self, size: u64) -> Result<()> {
+        allocate(&self.0.file, size)
+            .map_err(|err| Error::new(err, "allocating space for", self.0.path.clone().into()))
+    }
+
     /// Changes the permissions on the underlying file.
     ///
     /// This function is identical to [std::fs::File::set_permissions][0] except:
@@ -155,6 +191,30 @@ impl FileWrite {
             .map_err(|err| Error::new(err, "writing", self.0.path.clone().into()))
     }
 
+    /// Write the `str` followed by a `\n`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::PathFile;
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.txt");
+    /// let file = PathFile::create(example)?;
+    /// let mut write = file.open_append()?;
+    /// write.write_line("foo")?;
+    /// write.write_line("bar")?;
+    /// write.flush()?;
+    /// assert_eq!("foo\nbar\n", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_line(&mut self, s: &str) -> Result<()> {
+        self.write_str(s)?;
+        self.write_str("\n")
+    }
+
     /// `std::io::File::flush` buth with the new error type.
     pub fn flush(&mut self) -> Result<()> {
         self.0
@@ -162,6 +222,170 @@ impl FileWrite {
             .flush()
             .map_err(|err| Error::new(err, "flushing", self.0.path.clone().into()))
     }
+
+    /// Seek to the start of the file, the same as [`Seek::rewind`] but with the new error type.
+    ///
+    /// [`Seek::rewind`]: https://doc.rust-lang.org/std/io/trait.Seek.html#method.rewind
+    pub fn rewind(&mut self) -> Result<()> {
+        self.0
+            .file
+            .seek(io::SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|err| Error::new(err, "seeking", self.0.path.clone().into()))
+    }
+
+    /// Write `buf` starting at byte `offset`, returning the number of bytes written.
+    ///
+    /// This takes `&self` and doesn't need to seek first, which suits writing fixed-size records
+    /// into a preallocated file. Like [`Write::write`] it may write fewer bytes than `buf` holds.
+    ///
+    /// On Unix this uses `pwrite`, which does not change the position of the file. On Windows
+    /// the position of the file is moved to just after the bytes written. On other platforms it
+    /// returns `io::ErrorKind::Unsupported`.
+    ///
+    /// > Don't use this on a handle opened in append mode: on Linux the data is appended to the
+    /// > end of the file regardless of `offset`, and other platforms differ.
+    ///
+    /// [`Write::write`]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.write
+    ///
+    /// # Examples
+    /// ```rust
+    /// # extern crate path_abs;
+    /// # extern crate tempfile;
+    /// use path_abs::{FileWrite, PathFile};
+    ///
+    /// # fn try_main() -> ::std::io::Result<()> {
+    /// # let tmp = tempfile::TempDir::new()?;
+    /// # let example = &tmp.path().join("example.bin");
+    /// let file = PathFile::create(example)?;
+    /// file.write_str("aaaabbbbcccc")?;
+    ///
+    /// let write = FileWrite::open_edit(&file)?;
+    /// assert_eq!(4, write.write_at(b"BBBB", 4)?);
+    /// assert_eq!("aaaaBBBBcccc", file.read_string()?);
+    /// # Ok(()) } fn main() { try_main().unwrap() }
+    /// ```
+    pub fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize> {
+        self.0.write_at(buf, offset)
+    }
+}
+
+/// Make `file` at least `size` bytes long, reserving the space on disk where possible.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn allocate(file: &fs::File, size: u64) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    if size == 0 {
+        return Ok(());
+    }
+    // `posix_fallocate` returns the error instead of setting `errno`.
+    match unsafe { ::libc::posix_fallocate(file.as_raw_fd(), 0, size as ::libc::off_t) } {
+        0 => Ok(()),
+        ::libc::EINVAL | ::libc::EOPNOTSUPP => extend(file, size),
+        err => Err(io::Error::from_raw_os_error(err)),
+    }
+}
+
+/// Make `file` at least `size` bytes long, reserving the space on disk where possible.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn allocate(file: &fs::File, size: u64) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let len = file.metadata()?.len();
+    if size <= len {
+        return Ok(());
+    }
+    // Allocate the missing space after the physical end of the file, preferring it contiguous.
+    let mut store = ::libc::fstore_t {
+        fst_flags: ::libc::F_ALLOCATECONTIG | ::libc::F_ALLOCATEALL,
+        fst_posmode: ::libc::F_PEOFPOSMODE,
+        fst_offset: 0,
+        fst_length: (size - len) as ::libc::off_t,
+        fst_bytesalloc: 0,
+    };
+    let fd = file.as_raw_fd();
+    let preallocate = |store: &mut ::libc::fstore_t| unsafe {
+        ::libc::fcntl(fd, ::libc::F_PREALLOCATE, store as *mut ::libc::fstore_t) != -1
+    };
+    if !preallocate(&mut store) {
+        store.fst_flags = ::libc::F_ALLOCATEALL;
+        if !preallocate(&mut store) {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(::libc::ENOTSUP) {
+                return Err(err);
+            }
+        }
+    }
+    file.set_len(size)
+}
+
+/// Make `file` at least `size` bytes long, reserving the space on disk where possible.
+#[cfg(windows)]
+fn allocate(file: &fs::File, size: u64) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    #[repr(C)]
+    struct FileAllocationInfo {
+        allocation_size: i64,
+    }
+    const FILE_ALLOCATION_INFO_CLASS: i32 = 5;
+    const ERROR_NOT_SUPPORTED: i32 = 50;
+    const ERROR_INVALID_PARAMETER: i32 = 87;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetFileInformationByHandle(
+            file: *mut ::std::ffi::c_void,
+            class: i32,
+            info: *const ::std::ffi::c_void,
+            size: u32,
+        ) -> i32;
+    }
+
+    let len = file.metadata()?.len();
+    if size <= len {
+        return Ok(());
+    }
+    let info = FileAllocationInfo {
+        allocation_size: size as i64,
+    };
+    let ok = unsafe {
+        SetFileInformationByHandle(
+            file.as_raw_handle() as *mut _,
+            FILE_ALLOCATION_INFO_CLASS,
+            &info as *const FileAllocationInfo as *const _,
+            ::std::mem::size_of::<FileAllocationInfo>() as u32,
+        )
+    };
+    if ok == 0 {
+        // The filesystem doesn't support preallocation, so just extend the file.
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(ERROR_NOT_SUPPORTED) | Some(ERROR_INVALID_PARAMETER) => (),
+            _ => return Err(err),
+        }
+    }
+    file.set_len(size)
+}
+
+/// Make `file` at least `size` bytes long. Preallocation isn't supported on this platform.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    windows
+)))]
+fn allocate(file: &fs::File, size: u64) -> io::Result<()> {
+    extend(file, size)
+}
+
+/// Extend `file` to `size` bytes with `set_len`, without shrinking it.
+#[cfg(not(any(target_os = "macos", target_os = "ios", windows)))]
+fn extend(file: &fs::File, size: u64) -> io::Result<()> {
+    if file.metadata()?.len() < size {
+        file.set_len(size)?;
+    }
+    Ok(())
 }
 
 impl fmt::Debug for FileWrite {
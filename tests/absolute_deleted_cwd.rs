@@ -0,0 +1,30 @@
+//! This file tests that PathAbs::new() never touches the current directory for fully specified
+//! absolute paths, so they still resolve after the current directory has been deleted.
+//!
+//! Windows doesn't allow deleting the current directory, so this is only tested on unix.
+//!
+//! The current directory is process-wide, so this is kept in its own test binary.
+#![cfg(unix)]
+
+use std::env;
+
+use path_abs::PathAbs;
+use tempfile::TempDir;
+
+#[test]
+fn absolute_path_with_deleted_cwd() {
+    let tmp_dir = TempDir::new().expect("create temp dir");
+    let deleted = tmp_dir.path().join("deleted");
+    std::fs::create_dir(&deleted).unwrap();
+    env::set_current_dir(&deleted).unwrap();
+    std::fs::remove_dir(&deleted).unwrap();
+
+    // Relative paths need the current directory...
+    assert!(PathAbs::new("foo").is_err());
+
+    // ...but absolute paths don't, even if they don't exist.
+    let actual = PathAbs::new("/does/not/../exist").unwrap();
+    assert_eq!(actual.as_path(), std::path::Path::new("/does/exist"));
+
+    env::set_current_dir(tmp_dir.path()).unwrap();
+}